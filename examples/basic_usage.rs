@@ -22,7 +22,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut file: FileHandle = dir.get_file_handle_with_options("hello.txt", &options).await?;
     
     // Write some data to the file
-    let write_options = CreateWritableOptions { keep_existing_data: false };
+    let write_options = CreateWritableOptions { keep_existing_data: false, atomic: false };
     let mut writer: WritableFileStream = file.create_writable_with_options(&write_options).await?;
     
     let message = b"Hello from OPFS! This works on both native and web platforms.";
@@ -0,0 +1,319 @@
+//! Serializes a [`DirectoryHandle`]'s subtree to (and restores it from) a tar byte stream,
+//! built on [`crate::walk::walk`] so entries stream out one at a time instead of buffering the
+//! whole tree in memory.
+//!
+//! This hand-rolls a minimal reader/writer for the USTAR header format rather than pulling in a
+//! tar dependency, matching the rest of the crate's preference for small self-contained pieces
+//! (see [`crate::walk::glob_match`]) over a crate for something this narrow. Only regular files
+//! and directories round-trip; entry names must fit USTAR's 100-byte un-prefixed name field
+//! with a terminating NUL, so they are limited to 99 bytes.
+
+use crate::{DirectoryEntry, DirectoryHandle, FileHandle, WritableFileStream};
+use futures::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use futures::{StreamExt, pin_mut};
+use std::path::{Component, Path, PathBuf};
+
+const BLOCK_SIZE: usize = 512;
+const TYPE_REGULAR: u8 = b'0';
+const TYPE_DIRECTORY: u8 = b'5';
+
+/// Options accepted by [`DirectoryHandle::import_tar`](crate::DirectoryHandle::import_tar).
+pub struct ImportTarOptions {
+    /// Whether to create directories and files that don't already exist under the handle.
+    pub create: bool,
+}
+
+impl Default for ImportTarOptions {
+    fn default() -> Self {
+        Self { create: true }
+    }
+}
+
+/// Failure modes specific to (de)serializing a tar stream, layered over a backend's own
+/// `Error` type.
+#[derive(Debug)]
+pub enum ArchiveError<E> {
+    Backend(E),
+    /// A file- or writable-stream-level operation failed (as opposed to a directory-level one).
+    FileBackend(E),
+    /// An entry's path escaped the import root via a `..` component.
+    PathTraversal(PathBuf),
+    /// An entry's name was too long to encode in a tar header while exporting. Import does not
+    /// validate headers; a truncated stream surfaces as [`ArchiveError::Io`].
+    Malformed(String),
+    Io(std::io::Error),
+}
+
+impl<E> From<std::io::Error> for ArchiveError<E> {
+    fn from(error: std::io::Error) -> Self {
+        ArchiveError::Io(error)
+    }
+}
+
+fn tar_name(path: &Path) -> String {
+    path.iter()
+        .map(|part| part.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn write_octal(field: &mut [u8], mut value: u64) {
+    let width = field.len() - 1;
+    for i in (0..width).rev() {
+        field[i] = b'0' + (value % 8) as u8;
+        value /= 8;
+    }
+    field[width] = 0;
+}
+
+fn write_name(field: &mut [u8], name: &str) -> Result<(), String> {
+    let bytes = name.as_bytes();
+    if bytes.len() >= field.len() {
+        return Err(format!(
+            "'{name}' is too long for a tar entry name (max {} bytes)",
+            field.len() - 1
+        ));
+    }
+    field[..bytes.len()].copy_from_slice(bytes);
+    Ok(())
+}
+
+fn header_block(
+    name: &str,
+    kind: u8,
+    size: u64,
+    metadata: Option<&crate::Metadata>,
+) -> Result<[u8; BLOCK_SIZE], String> {
+    let mut block = [0u8; BLOCK_SIZE];
+    write_name(&mut block[0..100], name)?;
+    let readonly = metadata.is_some_and(|metadata| metadata.readonly);
+    let mode = match (kind == TYPE_DIRECTORY, readonly) {
+        (true, _) => 0o755,
+        (false, true) => 0o444,
+        (false, false) => 0o644,
+    };
+    write_octal(&mut block[100..108], mode);
+    write_octal(&mut block[108..116], 0); // uid
+    write_octal(&mut block[116..124], 0); // gid
+    write_octal(&mut block[124..136], size);
+    let mtime = metadata
+        .and_then(|metadata| metadata.modified)
+        .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+        .map_or(0, |since_epoch| since_epoch.as_secs());
+    write_octal(&mut block[136..148], mtime);
+    block[148..156].fill(b' '); // checksum field, treated as spaces while summing
+    block[156] = kind;
+    block[257..263].copy_from_slice(b"ustar\0");
+    block[263] = b'0';
+    block[264] = b'0';
+
+    let checksum: u64 = block.iter().map(|&b| b as u64).sum();
+    let mut checksum_field = [b'0'; 7];
+    let mut value = checksum;
+    for i in (0..6).rev() {
+        checksum_field[i] = b'0' + (value % 8) as u8;
+        value /= 8;
+    }
+    checksum_field[6] = 0;
+    block[148..155].copy_from_slice(&checksum_field);
+    block[155] = b' ';
+
+    Ok(block)
+}
+
+fn read_octal(field: &[u8]) -> u64 {
+    let text = std::str::from_utf8(field).unwrap_or("");
+    let trimmed = text.trim_matches(|c: char| c == '\0' || c == ' ');
+    u64::from_str_radix(trimmed, 8).unwrap_or(0)
+}
+
+fn read_name(field: &[u8]) -> String {
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).to_string()
+}
+
+fn padding_len(size: usize) -> usize {
+    (BLOCK_SIZE - (size % BLOCK_SIZE)) % BLOCK_SIZE
+}
+
+pub async fn export_tar<D, W>(root: &D, writer: &mut W) -> Result<(), ArchiveError<D::Error>>
+where
+    D: DirectoryHandle,
+    W: AsyncWrite + Unpin,
+{
+    let entries = crate::walk::walk(root, &crate::WalkOptions::default())
+        .await
+        .map_err(ArchiveError::Backend)?;
+    pin_mut!(entries);
+
+    while let Some(entry) = entries.next().await {
+        let (path, entry) = entry.map_err(ArchiveError::Backend)?;
+
+        match entry {
+            DirectoryEntry::Directory(dir) => {
+                let metadata = dir.metadata().await.map_err(ArchiveError::Backend)?;
+                let name = format!("{}/", tar_name(&path));
+                let block = header_block(&name, TYPE_DIRECTORY, 0, Some(&metadata))
+                    .map_err(ArchiveError::Malformed)?;
+                writer.write_all(&block).await?;
+            }
+            DirectoryEntry::File(file) => {
+                let metadata = file.metadata().await.map_err(ArchiveError::FileBackend)?;
+                let data = file.read().await.map_err(ArchiveError::FileBackend)?;
+                let name = tar_name(&path);
+                let block = header_block(&name, TYPE_REGULAR, data.len() as u64, Some(&metadata))
+                    .map_err(ArchiveError::Malformed)?;
+                writer.write_all(&block).await?;
+                writer.write_all(&data).await?;
+
+                let padding = padding_len(data.len());
+                if padding > 0 {
+                    writer.write_all(&vec![0u8; padding]).await?;
+                }
+            }
+        }
+    }
+
+    // Two all-zero blocks mark the end of the archive.
+    writer.write_all(&[0u8; BLOCK_SIZE * 2]).await?;
+    Ok(())
+}
+
+pub async fn import_tar<D, R>(
+    root: &D,
+    reader: &mut R,
+    options: &ImportTarOptions,
+) -> Result<(), ArchiveError<D::Error>>
+where
+    D: DirectoryHandle,
+    R: AsyncRead + Unpin,
+{
+    loop {
+        let mut header = [0u8; BLOCK_SIZE];
+        reader.read_exact(&mut header).await?;
+        if header.iter().all(|&b| b == 0) {
+            break;
+        }
+
+        let name = read_name(&header[0..100]);
+        let size = read_octal(&header[124..136]) as usize;
+        let kind = header[156];
+
+        let relative = PathBuf::from(name.trim_end_matches('/'));
+        if relative
+            .components()
+            .any(|component| matches!(component, Component::ParentDir))
+        {
+            return Err(ArchiveError::PathTraversal(relative));
+        }
+
+        let mut parts: Vec<String> = relative
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+                _ => None,
+            })
+            .collect();
+
+        let file_name = if kind == TYPE_DIRECTORY {
+            None
+        } else {
+            parts.pop()
+        };
+
+        let mut dir = root.clone();
+        for part in &parts {
+            dir = dir
+                .get_directory_handle_with_options(
+                    part,
+                    &crate::GetDirectoryHandleOptions { create: options.create },
+                )
+                .await
+                .map_err(ArchiveError::Backend)?;
+        }
+
+        let mut data = vec![0u8; size];
+        reader.read_exact(&mut data).await?;
+        let padding = padding_len(size);
+        if padding > 0 {
+            let mut pad = vec![0u8; padding];
+            reader.read_exact(&mut pad).await?;
+        }
+
+        if let Some(file_name) = file_name {
+            let mut file = dir
+                .get_file_handle_with_options(
+                    &file_name,
+                    &crate::GetFileHandleOptions { create: options.create },
+                )
+                .await
+                .map_err(ArchiveError::Backend)?;
+
+            let mut writer = file.create_writable().await.map_err(ArchiveError::FileBackend)?;
+            writer
+                .write_at_cursor_pos(data)
+                .await
+                .map_err(ArchiveError::FileBackend)?;
+            writer.close().await.map_err(ArchiveError::FileBackend)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::DirectoryHandle;
+    use crate::{
+        DirectoryHandle as _, FileHandle as _, GetDirectoryHandleOptions, GetFileHandleOptions,
+        WritableFileStream as _,
+    };
+
+    async fn write_file(dir: &DirectoryHandle, name: &str, contents: &[u8]) {
+        let mut file = dir
+            .get_file_handle_with_options(name, &GetFileHandleOptions { create: true })
+            .await
+            .unwrap();
+        let mut writer = file.create_writable().await.unwrap();
+        writer.write_at_cursor_pos(contents.to_vec()).await.unwrap();
+        writer.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_export_then_import_round_trips_tree() {
+        let root = DirectoryHandle::default();
+        write_file(&root, "a.txt", b"hello from root").await;
+        let sub = root
+            .get_directory_handle_with_options("sub", &GetDirectoryHandleOptions { create: true })
+            .await
+            .unwrap();
+        write_file(&sub, "b.txt", b"hello from sub").await;
+
+        let mut archive = Vec::new();
+        export_tar(&root, &mut archive).await.unwrap();
+
+        let restored = DirectoryHandle::default();
+        import_tar(&restored, &mut archive.as_slice(), &ImportTarOptions::default())
+            .await
+            .unwrap();
+
+        let a = restored.get_file_handle("a.txt").await.unwrap();
+        assert_eq!(a.read().await.unwrap(), b"hello from root");
+
+        let restored_sub = restored.get_directory_handle("sub").await.unwrap();
+        let b = restored_sub.get_file_handle("b.txt").await.unwrap();
+        assert_eq!(b.read().await.unwrap(), b"hello from sub");
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_path_traversal() {
+        let block = header_block("../escape.txt", TYPE_REGULAR, 0, None).unwrap();
+        let mut archive = block.to_vec();
+        archive.extend_from_slice(&[0u8; BLOCK_SIZE * 2]);
+
+        let root = DirectoryHandle::default();
+        let result = import_tar(&root, &mut archive.as_slice(), &ImportTarOptions::default()).await;
+        assert!(matches!(result, Err(ArchiveError::PathTraversal(_))));
+    }
+}
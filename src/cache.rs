@@ -0,0 +1,546 @@
+//! A read-through, write-back in-memory cache layered on top of another backend.
+//!
+//! [`CachingDirectoryHandle`] wraps any [`DirectoryHandle`] so repeated reads of hot files
+//! avoid round-tripping through the backing store. Entries are tracked in an LRU order against
+//! a byte budget: reading a file (or closing a write) moves its entry to the most-recently-used
+//! end, and once the budget would be exceeded, least-recently-used entries are evicted —
+//! flushing any dirty, not-yet-written-back buffer to the backing store first. Writes land in
+//! the in-memory buffer and are only marked dirty at `close()`; the actual flush to the backing
+//! store happens lazily, only at eviction; an entry replaced by a newer write, or dropped by
+//! `remove_entry`/`copy_entry`, is not flushed.
+
+use crate::{DirectoryHandle, FileHandle, WritableFileStream};
+use futures::Stream;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::rc::Rc;
+
+type CacheDirectoryEntry<D> =
+    crate::DirectoryEntry<CachingDirectoryHandle<D>, CachingFileHandle<D>>;
+
+#[derive(Debug)]
+struct CacheEntry<F> {
+    data: Vec<u8>,
+    dirty: bool,
+    handle: F,
+}
+
+#[derive(Debug)]
+struct CacheStore<D: DirectoryHandle> {
+    budget_bytes: usize,
+    used_bytes: usize,
+    entries: HashMap<String, CacheEntry<D::FileHandleT>>,
+    /// Recency order: front is least-recently-used, back is most-recently-used.
+    order: VecDeque<String>,
+}
+
+impl<D: DirectoryHandle> CacheStore<D> {
+    fn touch(&mut self, key: &str) {
+        if let Some(pos) = self.order.iter().position(|k| k == key) {
+            self.order.remove(pos);
+        }
+        self.order.push_back(key.to_string());
+    }
+
+    fn forget(&mut self, key: &str) -> Option<CacheEntry<D::FileHandleT>> {
+        if let Some(pos) = self.order.iter().position(|k| k == key) {
+            self.order.remove(pos);
+        }
+        let entry = self.entries.remove(key)?;
+        self.used_bytes -= entry.data.len();
+        Some(entry)
+    }
+}
+
+async fn flush_entry<F: FileHandle>(entry: &mut CacheEntry<F>) -> Result<(), F::Error> {
+    let mut writer = entry
+        .handle
+        .create_writable_with_options(&crate::CreateWritableOptions {
+            keep_existing_data: false,
+            atomic: false,
+        })
+        .await?;
+    writer.write_at_cursor_pos(entry.data.clone()).await?;
+    writer.close().await?;
+    entry.dirty = false;
+    Ok(())
+}
+
+/// Evicts least-recently-used entries, flushing any that are dirty, until `incoming` more
+/// bytes would fit within the budget (or nothing is left to evict).
+async fn evict_until_fits<D: DirectoryHandle>(
+    store: &Rc<RefCell<CacheStore<D>>>,
+    incoming: usize,
+) -> Result<(), D::Error> {
+    loop {
+        let over_budget = {
+            let store = store.borrow();
+            store.used_bytes + incoming > store.budget_bytes
+        };
+        if !over_budget {
+            return Ok(());
+        }
+
+        let evicted = {
+            let mut store = store.borrow_mut();
+            let Some(key) = store.order.pop_front() else {
+                return Ok(()); // nothing left to evict; let the budget be exceeded
+            };
+            store.entries.remove(&key).map(|entry| {
+                store.used_bytes -= entry.data.len();
+                entry
+            })
+        };
+
+        if let Some(mut entry) = evicted {
+            if entry.dirty {
+                flush_entry(&mut entry).await?;
+            }
+        }
+    }
+}
+
+/// Evicts enough to make room for `entry`, then inserts it as the most-recently-used. Any
+/// existing entry under `key` is dropped first (without flushing — it's about to be
+/// superseded by `entry`'s data, which is always the more current version).
+async fn insert_entry<D: DirectoryHandle>(
+    store: &Rc<RefCell<CacheStore<D>>>,
+    key: String,
+    entry: CacheEntry<D::FileHandleT>,
+) -> Result<(), D::Error> {
+    store.borrow_mut().forget(&key);
+
+    evict_until_fits::<D>(store, entry.data.len()).await?;
+
+    let mut store_mut = store.borrow_mut();
+    store_mut.used_bytes += entry.data.len();
+    store_mut.entries.insert(key.clone(), entry);
+    store_mut.touch(&key);
+    Ok(())
+}
+
+/// A directory in the caching backend, wrapping an inner directory of backend `D`.
+#[derive(Clone, Debug)]
+pub struct CachingDirectoryHandle<D: DirectoryHandle> {
+    inner: D,
+    /// This directory's path relative to the cache root, used as the cache-entry key prefix
+    /// for its children. Empty for the root directory passed to [`Self::new`].
+    key_prefix: String,
+    store: Rc<RefCell<CacheStore<D>>>,
+}
+
+impl<D: DirectoryHandle> CachingDirectoryHandle<D> {
+    /// Wraps `inner` as the cache root, bounding the cache to `budget_bytes` of resident file
+    /// content.
+    pub fn new(inner: D, budget_bytes: usize) -> Self {
+        Self {
+            inner,
+            key_prefix: String::new(),
+            store: Rc::new(RefCell::new(CacheStore {
+                budget_bytes,
+                used_bytes: 0,
+                entries: HashMap::new(),
+                order: VecDeque::new(),
+            })),
+        }
+    }
+
+    fn child_key(&self, name: &str) -> String {
+        if self.key_prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}/{}", self.key_prefix, name)
+        }
+    }
+
+    fn wrap_file(&self, name: &str, inner: D::FileHandleT) -> CachingFileHandle<D> {
+        CachingFileHandle {
+            key: self.child_key(name),
+            inner,
+            store: self.store.clone(),
+        }
+    }
+
+    fn wrap_dir(&self, name: &str, inner: D) -> Self {
+        Self {
+            inner,
+            key_prefix: self.child_key(name),
+            store: self.store.clone(),
+        }
+    }
+}
+
+/// A file in the caching backend, wrapping an inner file of backend `D`.
+#[derive(Clone, Debug)]
+pub struct CachingFileHandle<D: DirectoryHandle> {
+    key: String,
+    inner: D::FileHandleT,
+    store: Rc<RefCell<CacheStore<D>>>,
+}
+
+/// A writable stream in the caching backend. Writes accumulate in a local buffer and are only
+/// handed to the cache (as a dirty entry) on [`close`](crate::WritableFileStream::close).
+#[derive(Clone, Debug)]
+pub struct CachingWritableFileStream<D: DirectoryHandle> {
+    key: String,
+    inner: D::FileHandleT,
+    store: Rc<RefCell<CacheStore<D>>>,
+    cursor_pos: usize,
+    buffer: Vec<u8>,
+}
+
+impl<D: DirectoryHandle> crate::private::Sealed for CachingDirectoryHandle<D> {}
+impl<D: DirectoryHandle> crate::private::Sealed for CachingFileHandle<D> {}
+impl<D: DirectoryHandle> crate::private::Sealed for CachingWritableFileStream<D> {}
+
+impl<D: DirectoryHandle> crate::DirectoryHandle for CachingDirectoryHandle<D> {
+    type Error = D::Error;
+    type FileHandleT = CachingFileHandle<D>;
+
+    async fn get_file_handle_with_options(
+        &self,
+        name: &str,
+        options: &crate::GetFileHandleOptions,
+    ) -> Result<Self::FileHandleT, Self::Error> {
+        let inner = self.inner.get_file_handle_with_options(name, options).await?;
+        Ok(self.wrap_file(name, inner))
+    }
+
+    async fn get_directory_handle_with_options(
+        &self,
+        name: &str,
+        options: &crate::GetDirectoryHandleOptions,
+    ) -> Result<Self, Self::Error> {
+        let inner = self
+            .inner
+            .get_directory_handle_with_options(name, options)
+            .await?;
+        Ok(self.wrap_dir(name, inner))
+    }
+
+    async fn metadata(&self) -> Result<crate::Metadata, Self::Error> {
+        self.inner.metadata().await
+    }
+
+    async fn remove_entry(&mut self, name: &str) -> Result<(), Self::Error> {
+        self.inner.remove_entry(name).await?;
+        self.store.borrow_mut().forget(&self.child_key(name));
+        Ok(())
+    }
+
+    async fn rename_entry(
+        &mut self,
+        from: &str,
+        to: &Self,
+        new_name: &str,
+    ) -> Result<(), Self::Error> {
+        self.inner.rename_entry(from, &to.inner, new_name).await?;
+
+        let old_key = self.child_key(from);
+        let entry = self.store.borrow_mut().forget(&old_key);
+        if let Some(entry) = entry {
+            let new_key = to.child_key(new_name);
+            insert_entry::<D>(&to.store, new_key, entry).await?;
+        }
+        Ok(())
+    }
+
+    async fn copy_entry(
+        &mut self,
+        name: &str,
+        dest: &Self,
+        new_name: &str,
+        options: &crate::CopyOptions,
+    ) -> Result<(), Self::Error> {
+        self.inner.copy_entry(name, &dest.inner, new_name, options).await?;
+
+        // The destination may already have a stale cache entry (e.g. an overwrite); invalidate
+        // it so the next read reflects the freshly copied contents instead of whatever was
+        // cached before. The backing store is the source of truth now; the cache repopulates
+        // lazily on the next read.
+        dest.store.borrow_mut().forget(&dest.child_key(new_name));
+        Ok(())
+    }
+
+    async fn remove_entry_with_options(
+        &mut self,
+        name: &str,
+        options: &crate::FileSystemRemoveOptions,
+    ) -> Result<(), Self::Error> {
+        self.inner.remove_entry_with_options(name, options).await?;
+        self.store.borrow_mut().forget(&self.child_key(name));
+        Ok(())
+    }
+
+    async fn entries(
+        &self,
+    ) -> Result<
+        impl Stream<Item = Result<(String, CacheDirectoryEntry<D>), Self::Error>>,
+        Self::Error,
+    > {
+        use futures::{StreamExt, pin_mut};
+
+        let inner_entries = self.inner.entries().await?;
+        pin_mut!(inner_entries);
+
+        let mut mapped = Vec::new();
+        while let Some(entry) = inner_entries.next().await {
+            let (name, entry) = entry?;
+            let mapped_entry = match entry {
+                crate::DirectoryEntry::File(file) => {
+                    crate::DirectoryEntry::File(self.wrap_file(&name, file))
+                }
+                crate::DirectoryEntry::Directory(dir) => {
+                    crate::DirectoryEntry::Directory(self.wrap_dir(&name, dir))
+                }
+            };
+            mapped.push(Ok((name, mapped_entry)));
+        }
+
+        Ok(futures::stream::iter(mapped))
+    }
+
+    async fn watch_with_options(
+        &self,
+        options: &crate::WatchOptions,
+    ) -> Result<impl Stream<Item = crate::ChangeEvent> + 'static, Self::Error> {
+        self.inner.watch_with_options(options).await
+    }
+}
+
+impl<D: DirectoryHandle> crate::FileHandle for CachingFileHandle<D> {
+    type Error = <D::FileHandleT as FileHandle>::Error;
+    type WritableFileStreamT = CachingWritableFileStream<D>;
+
+    async fn create_writable_with_options(
+        &mut self,
+        options: &crate::CreateWritableOptions,
+    ) -> Result<Self::WritableFileStreamT, Self::Error> {
+        let buffer = if options.keep_existing_data {
+            self.read().await?
+        } else {
+            Vec::new()
+        };
+
+        Ok(CachingWritableFileStream {
+            key: self.key.clone(),
+            inner: self.inner.clone(),
+            store: self.store.clone(),
+            cursor_pos: 0,
+            buffer,
+        })
+    }
+
+    async fn read(&self) -> Result<Vec<u8>, Self::Error> {
+        let cached = {
+            let mut store = self.store.borrow_mut();
+            if store.entries.contains_key(&self.key) {
+                store.touch(&self.key);
+                Some(store.entries[&self.key].data.clone())
+            } else {
+                None
+            }
+        };
+        if let Some(data) = cached {
+            return Ok(data);
+        }
+
+        let data = self.inner.read().await?;
+        insert_entry::<D>(
+            &self.store,
+            self.key.clone(),
+            CacheEntry {
+                data: data.clone(),
+                dirty: false,
+                handle: self.inner.clone(),
+            },
+        )
+        .await?;
+        Ok(data)
+    }
+
+    async fn size(&self) -> Result<usize, Self::Error> {
+        if let Some(len) = self
+            .store
+            .borrow()
+            .entries
+            .get(&self.key)
+            .map(|entry| entry.data.len())
+        {
+            return Ok(len);
+        }
+        self.inner.size().await
+    }
+
+    async fn read_range(&self, offset: usize, len: usize) -> Result<Vec<u8>, Self::Error> {
+        let data = self.read().await?;
+        if offset >= data.len() {
+            return Ok(Vec::new());
+        }
+        let end = (offset + len).min(data.len());
+        Ok(data[offset..end].to_vec())
+    }
+
+    async fn read_stream(
+        &self,
+    ) -> Result<impl Stream<Item = Result<bytes::Bytes, Self::Error>>, Self::Error> {
+        let data = self.read().await?;
+        Ok(futures::stream::iter(vec![Ok(bytes::Bytes::from(data))]))
+    }
+
+    async fn metadata(&self) -> Result<crate::Metadata, Self::Error> {
+        self.inner.metadata().await
+    }
+
+    async fn set_readonly(&mut self, readonly: bool) -> Result<(), Self::Error> {
+        self.inner.set_readonly(readonly).await
+    }
+}
+
+impl<D: DirectoryHandle> crate::WritableFileStream for CachingWritableFileStream<D> {
+    type Error = <<D::FileHandleT as FileHandle>::WritableFileStreamT as WritableFileStream>::Error;
+
+    async fn write_at_cursor_pos(&mut self, data: Vec<u8>) -> Result<(), Self::Error> {
+        let end = self.cursor_pos + data.len();
+        if end > self.buffer.len() {
+            self.buffer.resize(end, 0);
+        }
+        self.buffer[self.cursor_pos..end].copy_from_slice(&data);
+        self.cursor_pos = end;
+        Ok(())
+    }
+
+    async fn close(&mut self) -> Result<(), Self::Error> {
+        insert_entry::<D>(
+            &self.store,
+            self.key.clone(),
+            CacheEntry {
+                data: self.buffer.clone(),
+                dirty: true,
+                handle: self.inner.clone(),
+            },
+        )
+        .await
+    }
+
+    async fn seek(&mut self, offset: usize) -> Result<(), Self::Error> {
+        self.cursor_pos = offset;
+        Ok(())
+    }
+
+    async fn truncate(&mut self, size: usize) -> Result<(), Self::Error> {
+        self.buffer.resize(size, 0);
+        self.cursor_pos = self.cursor_pos.min(size);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        DirectoryHandle as _, FileHandle as _, GetFileHandleOptions, WritableFileStream as _,
+    };
+
+    async fn write_file(dir: &crate::memory::DirectoryHandle, name: &str, contents: &[u8]) {
+        let mut file = dir
+            .get_file_handle_with_options(name, &GetFileHandleOptions { create: true })
+            .await
+            .unwrap();
+        let mut writer = file.create_writable().await.unwrap();
+        writer.write_at_cursor_pos(contents.to_vec()).await.unwrap();
+        writer.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_read_populates_cache_and_hits_on_second_read() {
+        let backing = crate::memory::DirectoryHandle::default();
+        write_file(&backing, "a.txt", b"hello").await;
+
+        let cached = CachingDirectoryHandle::new(backing, 1024);
+        let file = cached.get_file_handle("a.txt").await.unwrap();
+
+        assert_eq!(file.read().await.unwrap(), b"hello");
+        assert!(cached.store.borrow().entries.contains_key("a.txt"));
+        assert_eq!(file.read().await.unwrap(), b"hello");
+    }
+
+    #[tokio::test]
+    async fn test_close_marks_dirty_and_eviction_flushes_to_backing_store() {
+        let backing = crate::memory::DirectoryHandle::default();
+        let cached = CachingDirectoryHandle::new(backing, 10);
+
+        let mut file_a = cached
+            .get_file_handle_with_options("a.txt", &GetFileHandleOptions { create: true })
+            .await
+            .unwrap();
+        let mut writer_a = file_a.create_writable().await.unwrap();
+        writer_a.write_at_cursor_pos(b"12345678".to_vec()).await.unwrap();
+        writer_a.close().await.unwrap();
+
+        // Within budget so far, and not yet flushed to the backing store -- just dirty.
+        assert!(cached.store.borrow().entries.get("a.txt").unwrap().dirty);
+
+        // A second 8-byte file pushes total usage over the 10-byte budget, forcing "a.txt"
+        // to be evicted, which must flush it to the backing store first.
+        let mut file_b = cached
+            .get_file_handle_with_options("b.txt", &GetFileHandleOptions { create: true })
+            .await
+            .unwrap();
+        let mut writer_b = file_b.create_writable().await.unwrap();
+        writer_b.write_at_cursor_pos(b"abcdefgh".to_vec()).await.unwrap();
+        writer_b.close().await.unwrap();
+
+        assert!(!cached.store.borrow().entries.contains_key("a.txt"));
+        assert_eq!(file_a.read().await.unwrap(), b"12345678");
+    }
+
+    #[tokio::test]
+    async fn test_entry_metadata_uses_default_impl_generically() {
+        // Regression test: DirectoryHandle::entry_metadata()'s default impl calls
+        // self.get_file_handle(name) and then .metadata() on the file, which only type-checks generically
+        // (here, with D::FileHandleT generic in D) because DirectoryHandle::FileHandleT ties its
+        // Error back to the outer trait's Error.
+        let backing = crate::memory::DirectoryHandle::default();
+        write_file(&backing, "a.txt", b"hello").await;
+
+        let cached = CachingDirectoryHandle::new(backing, 1024);
+        let metadata = crate::DirectoryHandle::entry_metadata(&cached, "a.txt").await.unwrap();
+        assert_eq!(metadata.len, 5);
+    }
+
+    #[tokio::test]
+    async fn test_rename_entry_within_same_directory_does_not_panic() {
+        // Regression test: a plain rename passes `to` as another handle onto this same
+        // directory, so `to.store` is the same Rc<RefCell<_>> as `self.store`. Holding the
+        // forget() borrow alive across the insert_entry().await call used to panic with
+        // "already borrowed" in exactly this case.
+        let backing = crate::memory::DirectoryHandle::default();
+        write_file(&backing, "a.txt", b"hello").await;
+
+        let mut cached = CachingDirectoryHandle::new(backing, 1024);
+        let file = cached.get_file_handle("a.txt").await.unwrap();
+        file.read().await.unwrap();
+        assert!(cached.store.borrow().entries.contains_key("a.txt"));
+
+        let same_dir = cached.clone();
+        cached.rename_entry("a.txt", &same_dir, "b.txt").await.unwrap();
+
+        assert!(!cached.store.borrow().entries.contains_key("a.txt"));
+        assert!(cached.store.borrow().entries.contains_key("b.txt"));
+    }
+
+    #[tokio::test]
+    async fn test_remove_entry_invalidates_cache() {
+        let backing = crate::memory::DirectoryHandle::default();
+        write_file(&backing, "a.txt", b"hello").await;
+
+        let mut cached = CachingDirectoryHandle::new(backing, 1024);
+        let file = cached.get_file_handle("a.txt").await.unwrap();
+        file.read().await.unwrap();
+        assert!(cached.store.borrow().entries.contains_key("a.txt"));
+
+        cached.remove_entry("a.txt").await.unwrap();
+        assert!(!cached.store.borrow().entries.contains_key("a.txt"));
+    }
+}
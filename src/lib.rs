@@ -1,9 +1,39 @@
-use std::fmt::Debug;
+//! Cross-platform file system abstraction modeled on the Web FileSystem Access API.
+//!
+//! [`DirectoryHandle`], [`FileHandle`], and [`WritableFileStream`] mirror
+//! `FileSystemDirectoryHandle`, `FileSystemFileHandle`, and `FileSystemWritableFileStream`
+//! respectively, so the same calling code can target the browser's Origin Private File System
+//! ([`web`]), a real on-disk directory ([`native`]), or a pure in-memory backend ([`memory`])
+//! useful for tests. [`persistent`] re-exports whichever of `native`/`web` fits the current
+//! target.
+//!
+//! Code that wants to stay agnostic of which backend it runs against should depend on these
+//! traits directly (generic over `D: DirectoryHandle`) rather than a concrete handle type;
+//! [`memory::DirectoryHandle`] is a complete, dependency-free stand-in for exercising that code
+//! in tests without a browser or a real disk.
+
+use bytes::Bytes;
 use futures::Stream;
-use futures::StreamExt;
-use js_sys::{ArrayBuffer, AsyncIterator, Uint8Array};
-use wasm_bindgen::{JsCast, JsValue};
-use wasm_bindgen_futures::{JsFuture, stream::JsStream};
+use std::fmt::Debug;
+
+mod private {
+    pub trait Sealed {}
+}
+
+mod archive;
+pub mod cache;
+pub mod memory;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod native;
+pub mod overlay;
+pub mod persistent;
+pub mod tarfs;
+#[cfg(target_arch = "wasm32")]
+pub mod web;
+mod walk;
+
+pub use archive::{ArchiveError, ImportTarOptions};
+pub use walk::{NamePattern, SearchQuery, WalkOptions};
 
 pub struct GetFileHandleOptions {
     pub create: bool,
@@ -31,12 +61,18 @@ impl Default for GetDirectoryHandleOptions {
 
 pub struct CreateWritableOptions {
     pub keep_existing_data: bool,
+    /// When set, writes land in a sibling temporary file that is only swapped into place on
+    /// [`WritableFileStream::close`], so a crash or error midway through writing never leaves
+    /// a half-written destination file. Only the [`native`] backend acts on this flag; the
+    /// [`memory`], [`web`] and [`cache`] backends ignore it.
+    pub atomic: bool,
 }
 
 impl Default for CreateWritableOptions {
     fn default() -> Self {
         Self {
             keep_existing_data: false,
+            atomic: false,
         }
     }
 }
@@ -53,328 +89,323 @@ impl Default for FileSystemRemoveOptions {
     }
 }
 
-#[derive(Debug, Clone)]
-pub enum DirectoryEntry {
-    File(FileHandle),
-    Directory(DirectoryHandle),
+/// Options accepted by [`DirectoryHandle::copy_entry`].
+pub struct CopyOptions {
+    /// Whether an existing entry at the destination name should be replaced.
+    pub overwrite: bool,
+    /// Whether copying a directory entry (rather than a file) is allowed at all; copying a
+    /// directory without this set is an error.
+    pub recursive: bool,
 }
 
-/// Returns the Origin Private File System's root directory.
-pub async fn storage_directory() -> std::io::Result<DirectoryHandle> {
-    use wasm_bindgen_futures::JsFuture;
-    use web_sys::FileSystemDirectoryHandle;
-
-    let window = web_sys::window().ok_or(std::io::Error::new(std::io::ErrorKind::Other, "No window object"))?;
-    let navigator = window.navigator();
-
-    let root_directory_handle =
-        FileSystemDirectoryHandle::from(map_io_result(JsFuture::from(navigator.storage().get_directory()).await)?);
-
-    Ok(DirectoryHandle::from(root_directory_handle))
+impl Default for CopyOptions {
+    fn default() -> Self {
+        Self {
+            overwrite: false,
+            recursive: false,
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
-pub struct DirectoryHandle(web_sys::FileSystemDirectoryHandle);
+/// Options accepted by [`DirectoryHandle::watch_with_options`].
+pub struct WatchOptions {
+    /// Whether to also watch the directory's subtree, not just its immediate children.
+    pub recursive: bool,
+}
 
-#[derive(Debug, Clone)]
-pub struct FileHandle(web_sys::FileSystemFileHandle);
+impl WatchOptions {
+    /// Kept as an associated const (rather than only via [`Default`]) so [`DirectoryHandle::watch`]
+    /// can borrow it as a promoted `'static` reference instead of a local temporary.
+    const DEFAULT: Self = Self { recursive: true };
+}
 
-#[derive(Debug, Clone)]
-pub struct WritableFileStream(web_sys::FileSystemWritableFileStream);
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
 
-#[derive(Debug, Clone)]
-pub struct Blob(web_sys::Blob);
+/// The kind of change a [`ChangeEvent`] reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Created,
+    Modified,
+    Removed,
+    Renamed,
+}
 
+/// A single filesystem change reported by [`DirectoryHandle::watch`]. The [`native`] backend
+/// debounces these; the [`memory`] backend reports every change as it happens.
 #[derive(Debug, Clone)]
-pub struct File(web_sys::File);
+pub struct ChangeEvent {
+    pub kind: ChangeKind,
+    /// Path of the affected entry, relative to the watched directory.
+    pub path: std::path::PathBuf,
+}
 
+/// An entry yielded while listing a directory's contents.
 #[derive(Debug, Clone)]
-pub struct FileList(web_sys::FileList);
-
-pub struct FileListIter {
-    list: FileList,
-    index: usize,
+pub enum DirectoryEntry<D, F> {
+    File(F),
+    Directory(D),
 }
 
-impl From<web_sys::FileSystemDirectoryHandle> for DirectoryHandle {
-    fn from(handle: web_sys::FileSystemDirectoryHandle) -> Self {
-        Self(handle)
-    }
+/// What kind of entry a [`Metadata`] describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileType {
+    File,
+    Directory,
+    Symlink,
 }
 
-impl From<web_sys::FileSystemFileHandle> for FileHandle {
-    fn from(handle: web_sys::FileSystemFileHandle) -> Self {
-        Self(handle)
+impl FileType {
+    pub fn is_file(self) -> bool {
+        matches!(self, FileType::File)
     }
-}
 
-impl From<web_sys::FileSystemWritableFileStream> for WritableFileStream {
-    fn from(handle: web_sys::FileSystemWritableFileStream) -> Self {
-        Self(handle)
+    pub fn is_dir(self) -> bool {
+        matches!(self, FileType::Directory)
     }
-}
 
-impl From<web_sys::Blob> for Blob {
-    fn from(handle: web_sys::Blob) -> Self {
-        Self(handle)
+    pub fn is_symlink(self) -> bool {
+        matches!(self, FileType::Symlink)
     }
 }
 
-impl From<web_sys::File> for File {
-    fn from(handle: web_sys::File) -> Self {
-        Self(handle)
-    }
+/// Timestamps, size, and permission info for a [`FileHandle`] or [`DirectoryHandle`].
+///
+/// Backends that can't supply a given timestamp (e.g. creation time isn't tracked on every
+/// platform) leave it `None` rather than guessing.
+#[derive(Debug, Clone)]
+pub struct Metadata {
+    pub len: u64,
+    pub file_type: FileType,
+    pub created: Option<std::time::SystemTime>,
+    pub modified: Option<std::time::SystemTime>,
+    pub accessed: Option<std::time::SystemTime>,
+    pub readonly: bool,
 }
 
-impl From<web_sys::FileList> for FileList {
-    fn from(handle: web_sys::FileList) -> Self {
-        Self(handle)
-    }
-}
+/// A handle to a directory, generic over backend.
+pub trait DirectoryHandle: private::Sealed + Clone + Debug + Sized {
+    type Error: Debug;
+    type FileHandleT: FileHandle<Error = Self::Error>;
 
-impl DirectoryHandle {
-    pub async fn get_file_handle(&self, name: &str) -> std::io::Result<FileHandle> {
+    async fn get_file_handle(&self, name: &str) -> Result<Self::FileHandleT, Self::Error> {
         self.get_file_handle_with_options(name, &Default::default()).await
     }
 
-    pub async fn get_file_handle_with_options(
+    async fn get_file_handle_with_options(
         &self,
         name: &str,
-        options: &crate::GetFileHandleOptions,
-    ) -> std::io::Result<FileHandle> {
-        let fs_options = web_sys::FileSystemGetFileOptions::new();
-        fs_options.set_create(options.create);
-        let file_system_file_handle = web_sys::FileSystemFileHandle::from(
-            map_io_result(JsFuture::from(self.0.get_file_handle_with_options(name, &fs_options)).await)?,
-        );
-        Ok(FileHandle(file_system_file_handle))
-    }
+        options: &GetFileHandleOptions,
+    ) -> Result<Self::FileHandleT, Self::Error>;
 
-    pub async fn get_directory_handle(&self, name: &str) -> std::io::Result<Self> {
+    async fn get_directory_handle(&self, name: &str) -> Result<Self, Self::Error> {
         self.get_directory_handle_with_options(name, &Default::default()).await
     }
 
-    pub async fn get_directory_handle_with_options(
+    async fn get_directory_handle_with_options(
         &self,
         name: &str,
-        options: &crate::GetDirectoryHandleOptions,
-    ) -> std::io::Result<Self> {
-        let fs_options = web_sys::FileSystemGetDirectoryOptions::new();
-        fs_options.set_create(options.create);
-        let file_system_directory_handle = web_sys::FileSystemDirectoryHandle::from(
-            map_io_result(JsFuture::from(self.0.get_directory_handle_with_options(name, &fs_options)).await)?,
-        );
-        Ok(DirectoryHandle(file_system_directory_handle))
+        options: &GetDirectoryHandleOptions,
+    ) -> Result<Self, Self::Error>;
+
+    /// Returns size, timestamps, and permission info for this directory itself (not its
+    /// contents). See [`FileHandle::metadata`] for files.
+    async fn metadata(&self) -> Result<Metadata, Self::Error>;
+
+    /// Returns size, timestamps, and permission info for the entry named `name`, without the
+    /// caller having to know ahead of time whether it's a file or a directory (and, for files,
+    /// without opening a full [`FileHandle`]). The default implementation probes
+    /// [`Self::get_file_handle`] first and falls back to [`Self::get_directory_handle`];
+    /// backends that can stat a name directly override this for a single round trip.
+    async fn entry_metadata(&self, name: &str) -> Result<Metadata, Self::Error> {
+        match self.get_file_handle(name).await {
+            Ok(file) => file.metadata().await,
+            Err(_) => self.get_directory_handle(name).await?.metadata().await,
+        }
     }
 
-    pub async fn remove_entry(&mut self, name: &str) -> std::io::Result<()> {
-        map_io_result(JsFuture::from(self.0.remove_entry(name)).await)?;
-        Ok(())
-    }
+    async fn remove_entry(&mut self, name: &str) -> Result<(), Self::Error>;
 
-    pub async fn remove_entry_with_options(
+    /// Moves `from` in this directory to `new_name` under `to`, which may be a different
+    /// handle to this same directory (a plain rename) or a genuinely different directory
+    /// (a move).
+    async fn rename_entry(&mut self, from: &str, to: &Self, new_name: &str)
+    -> Result<(), Self::Error>;
+
+    /// Copies `name` in this directory to `new_name` under `dest`. Copying a directory entry
+    /// requires [`CopyOptions::recursive`]; [`CopyOptions::overwrite`] controls whether an
+    /// existing `new_name` at the destination is replaced.
+    async fn copy_entry(
         &mut self,
         name: &str,
-        options: &FileSystemRemoveOptions,
-    ) -> std::io::Result<()> {
-        let fs_options = web_sys::FileSystemRemoveOptions::new();
-        fs_options.set_recursive(options.recursive);
-        map_io_result(JsFuture::from(self.0.remove_entry_with_options(name, &fs_options)).await)?;
-        Ok(())
+        dest: &Self,
+        new_name: &str,
+        options: &CopyOptions,
+    ) -> Result<(), Self::Error>;
+
+    async fn remove_entry_with_options(
+        &mut self,
+        name: &str,
+        _options: &FileSystemRemoveOptions,
+    ) -> Result<(), Self::Error> {
+        self.remove_entry(name).await
     }
 
-    pub async fn entries(
+    async fn entries(
         &self,
-    ) -> std::io::Result<impl Stream<Item = std::io::Result<(String, DirectoryEntry)>>>
-    {
-        let entries_iterator = self.0.entries();
-        let async_iterator = AsyncIterator::from(entries_iterator);
-        let js_stream: JsStream = JsStream::from(async_iterator);
-
-        let stream = js_stream.map(|item| {
-            map_io_result(match item {
-                Ok(js_array) => {
-                    // entries() returns [key, value] pairs
-                    let array = js_sys::Array::from(&js_array);
-                    let filename = array
-                        .get(0)
-                        .as_string()
-                        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::Other, "Invalid filename"))?;
-                    let handle = array.get(1);
-
-                    // Determine if it's a file or directory handle
-                    let entry = if handle.has_type::<web_sys::FileSystemFileHandle>() {
-                        DirectoryEntry::File(FileHandle(web_sys::FileSystemFileHandle::from(handle)))
-                    } else if handle.has_type::<web_sys::FileSystemDirectoryHandle>() {
-                        DirectoryEntry::Directory(DirectoryHandle(web_sys::FileSystemDirectoryHandle::from(
-                            handle,
-                        )))
-                    } else {
-                        return Err(std::io::Error::new(std::io::ErrorKind::Other, "Unknown handle type"));
-                    };
-
-                    Ok((filename, entry))
-                }
-                Err(e) => Err(e),
-            })
-        });
-
-        Ok(stream)
-    }
-}
+    ) -> Result<
+        impl Stream<Item = Result<(String, DirectoryEntry<Self, Self::FileHandleT>), Self::Error>>,
+        Self::Error,
+    >;
 
-impl FileHandle {
-    pub async fn create_writable(&mut self) -> std::io::Result<WritableFileStream> {
-        self.create_writable_with_options(&Default::default()).await
+    /// Recursively lists every descendant of this directory. See [`walk::walk`].
+    async fn walk(
+        &self,
+    ) -> Result<
+        impl Stream<Item = Result<(std::path::PathBuf, DirectoryEntry<Self, Self::FileHandleT>), Self::Error>>,
+        Self::Error,
+    > {
+        walk::walk(self, &WalkOptions::default()).await
     }
 
-    pub async fn create_writable_with_options(
-        &mut self,
-        options: &crate::CreateWritableOptions,
-    ) -> std::io::Result<WritableFileStream> {
-        let fs_options = web_sys::FileSystemCreateWritableOptions::new();
-        fs_options.set_keep_existing_data(options.keep_existing_data);
-        let file_system_writable_file_stream = web_sys::FileSystemWritableFileStream::unchecked_from_js(
-            map_io_result(JsFuture::from(self.0.create_writable_with_options(&fs_options)).await)?,
-        );
-        Ok(WritableFileStream(file_system_writable_file_stream))
+    /// Recursively lists every descendant of this directory, as [`Self::walk`] but with control
+    /// over traversal depth and symlink handling.
+    async fn walk_with_options(
+        &self,
+        options: &WalkOptions,
+    ) -> Result<
+        impl Stream<Item = Result<(std::path::PathBuf, DirectoryEntry<Self, Self::FileHandleT>), Self::Error>>,
+        Self::Error,
+    > {
+        walk::walk(self, options).await
     }
 
-    pub async fn read(&self) -> std::io::Result<Vec<u8>> {
-        self.get_blob().await?.binary().await
+    /// Searches this directory's subtree for files whose name (and, optionally, contents)
+    /// match `query`. See [`walk::search`].
+    async fn search(
+        &self,
+        query: &SearchQuery,
+    ) -> Result<impl Stream<Item = Result<(std::path::PathBuf, Self::FileHandleT), Self::Error>>, Self::Error> {
+        walk::search(self, query).await
+    }
+
+    /// Sums [`FileHandle::size`] across every file in this directory's subtree, built on
+    /// [`Self::walk`] so it works uniformly across backends.
+    async fn recursive_size(&self) -> Result<u64, Self::Error> {
+        let entries = self.walk().await?;
+        futures::pin_mut!(entries);
+
+        let mut total = 0u64;
+        while let Some(entry) = futures::StreamExt::next(&mut entries).await {
+            if let (_, DirectoryEntry::File(file)) = entry? {
+                total += file.size().await? as u64;
+            }
+        }
+        Ok(total)
     }
 
-    pub async fn size(&self) -> std::io::Result<usize> {
-        let size = self.get_blob().await?.size();
-        Ok(size)
+    /// Watches this directory for external changes, yielding a stream of [`ChangeEvent`]s
+    /// (debounced on the [`native`] backend). Dropping the stream tears down the underlying
+    /// watcher.
+    async fn watch(&self) -> Result<impl Stream<Item = ChangeEvent> + 'static, Self::Error> {
+        self.watch_with_options(&WatchOptions::DEFAULT).await
     }
-}
 
-impl FileHandle {
-    pub async fn get_blob(&self) -> std::io::Result<Blob> {
-        let file: web_sys::Blob = map_io_result(JsFuture::from(self.0.get_file()).await)?.into();
-        Ok(Blob(file))
-    }
+    async fn watch_with_options(
+        &self,
+        options: &WatchOptions,
+    ) -> Result<impl Stream<Item = ChangeEvent> + 'static, Self::Error>;
 
-    pub async fn get_file(&self) -> std::io::Result<File> {
-        let file: web_sys::File = map_io_result(JsFuture::from(self.0.get_file()).await)?.into();
-        Ok(File(file))
+    /// Serializes this directory's subtree to `writer` as a tar stream, walking it via
+    /// [`Self::walk`] so entries stream out without buffering the whole tree in memory. See
+    /// [`archive::export_tar`].
+    async fn export_tar<W: futures::io::AsyncWrite + Unpin>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), ArchiveError<Self::Error>> {
+        archive::export_tar(self, writer).await
     }
-}
 
-impl WritableFileStream {
-    pub async fn write(&mut self, data: Vec<u8>) -> std::io::Result<()> {
-        // You'd think we could just do
-        // ```
-        // JsFuture::from(self.0.write_with_u8_array(data.as_mut_slice())?).await?;
-        // ```
-        // But a safari bug makes this write basically the entire wasm heap to the file.
-        // So we have to write as a blob first.
-
-        let uint8_array = js_sys::Uint8Array::from(data.as_slice());
-        let array = js_sys::Array::new();
-        array.push(&uint8_array);
-        let blob = map_io_result(web_sys::Blob::new_with_u8_array_sequence(&array))?;
-
-        map_io_result(JsFuture::from(map_io_result(self.0.write_with_blob(&blob))?).await)?;
-        Ok(())
+    /// Recreates a tree previously written by [`Self::export_tar`] underneath this directory,
+    /// rejecting any entry whose path would escape the root via `..`. See
+    /// [`archive::import_tar`].
+    async fn import_tar<R: futures::io::AsyncRead + Unpin>(
+        &self,
+        reader: &mut R,
+        options: &ImportTarOptions,
+    ) -> Result<(), ArchiveError<Self::Error>> {
+        archive::import_tar(self, reader, options).await
     }
+}
 
-    pub async fn close(&mut self) -> std::io::Result<()> {
-        map_io_result(JsFuture::from(self.0.close()).await)?;
-        Ok(())
-    }
+/// A handle to a file, generic over backend.
+pub trait FileHandle: private::Sealed + Clone + Debug {
+    type Error: Debug;
+    type WritableFileStreamT: WritableFileStream<Error = Self::Error>;
 
-    pub async fn seek(&mut self, offset: usize) -> std::io::Result<()> {
-        map_io_result(JsFuture::from(map_io_result(self.0.seek_with_u32(offset as u32))?).await)?;
-        Ok(())
+    async fn create_writable(&mut self) -> Result<Self::WritableFileStreamT, Self::Error> {
+        self.create_writable_with_options(&Default::default()).await
     }
-}
 
-impl Blob {
-    pub fn size(&self) -> usize {
-        self.0.size() as usize
-    }
+    async fn create_writable_with_options(
+        &mut self,
+        options: &CreateWritableOptions,
+    ) -> Result<Self::WritableFileStreamT, Self::Error>;
 
-    pub async fn binary(&self) -> std::io::Result<Vec<u8>> {
-        let buffer = ArrayBuffer::unchecked_from_js(map_io_result(JsFuture::from(self.0.array_buffer()).await)?);
-        let uint8_array = Uint8Array::new(&buffer);
-        let mut vec = vec![0; self.size()];
-        uint8_array.copy_to(&mut vec);
-        Ok(vec)
-    }
+    /// Reads the whole file into memory. A thin convenience wrapper over [`Self::read_stream`];
+    /// prefer [`Self::read_range`], or [`Self::read_stream`] on a backend that reads in chunks,
+    /// for large files so the whole contents don't have to be buffered at once.
+    async fn read(&self) -> Result<Vec<u8>, Self::Error> {
+        use futures::TryStreamExt;
 
-    #[allow(dead_code)]
-    pub async fn text(&self) -> std::io::Result<String> {
-        map_io_result(JsFuture::from(self.0.text()).await)?
-            .as_string()
-            .ok_or(std::io::Error::new(std::io::ErrorKind::Other, "Unknown error"))
-    }
-}
+        let stream = self.read_stream().await?;
+        futures::pin_mut!(stream);
 
-impl File {
-    /// Last modified timestamp since the UNIX epoch.
-    pub fn last_modified(&self) -> std::time::SystemTime {
-        std::time::SystemTime::UNIX_EPOCH.checked_add(
-            std::time::Duration::from_millis(unsafe { self.0.last_modified().to_int_unchecked() })
-        ).unwrap()
+        let mut buffer = Vec::new();
+        while let Some(chunk) = stream.try_next().await? {
+            buffer.extend_from_slice(&chunk);
+        }
+        Ok(buffer)
     }
 
-    /// Filename.
-    pub fn name(&self) -> String {
-        self.0.name()
-    }
+    async fn size(&self) -> Result<usize, Self::Error>;
 
-    /// Returns the inherited `Blob` interface.
-    pub fn as_blob(&self) -> Blob {
-        Blob::from(self.0.clone().dyn_into::<web_sys::Blob>().unwrap())
-    }
-}
+    /// Reads only the `len` bytes starting at `offset`, without materializing the rest of the
+    /// file the way [`Self::read`] does. `len` is clamped to the bytes actually remaining in the
+    /// file, and an `offset` at or past EOF yields an empty `Vec` rather than an error,
+    /// mirroring object-store-style ranged GET ("page") semantics.
+    async fn read_range(&self, offset: usize, len: usize) -> Result<Vec<u8>, Self::Error>;
 
-impl FileList {
-    pub fn len(&self) -> usize {
-        self.0.length() as usize
-    }
+    /// Streams the file's contents in chunks. The [`native`] and [`web`] backends read the file
+    /// one fixed-size chunk at a time, so it is never held fully in memory; the [`memory`] and
+    /// [`tarfs`] backends split their already-resident bytes into fixed-size chunks; the
+    /// [`cache`] and [`overlay`] handles read the whole file and yield it as a single chunk.
+    async fn read_stream(
+        &self,
+    ) -> Result<impl Stream<Item = Result<Bytes, Self::Error>>, Self::Error>;
 
-    pub fn iter(&self) -> FileListIter {
-        self.clone().into_iter()
-    }
+    /// Returns size, timestamps, and permission info for this file.
+    async fn metadata(&self) -> Result<Metadata, Self::Error>;
 
-    pub fn get(&self, index: usize) -> Option<File> {
-        self.0.item(index as u32).map(|f| File::from(f))
-    }
+    /// Marks this file read-only (or clears the flag). See [`Metadata::readonly`].
+    async fn set_readonly(&mut self, readonly: bool) -> Result<(), Self::Error>;
 }
 
-impl std::iter::IntoIterator for FileList {
-    type Item = File;
-    type IntoIter = FileListIter;
+/// A handle used to write to a file opened via [`FileHandle::create_writable`].
+pub trait WritableFileStream: private::Sealed + Clone + Debug {
+    type Error: Debug;
 
-    fn into_iter(self) -> Self::IntoIter {
-        FileListIter {
-            list: self,
-            index: 0,
-        }
-    }
-}
+    async fn write_at_cursor_pos(&mut self, data: Vec<u8>) -> Result<(), Self::Error>;
 
-impl std::iter::Iterator for FileListIter {
-    type Item = File;
+    async fn close(&mut self) -> Result<(), Self::Error>;
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let Some(item) = self.list.0.item(self.index as u32) else {
-            return None;
-        };
-        self.index += 1;
-        Some(File::from(item))
-    }
-}
+    async fn seek(&mut self, offset: usize) -> Result<(), Self::Error>;
 
-fn map_io_result<T>(result: Result<T, JsValue>) -> std::io::Result<T> {
-    result.map_err(|e| {
-        use wasm_bindgen::JsCast;
-        let Ok(e) = e.dyn_into::<js_sys::Error>() else {
-            return std::io::Error::new(std::io::ErrorKind::Other, "Unknown error");
-        };
-        std::io::Error::new(std::io::ErrorKind::Other, e.to_string().as_string().unwrap())
-    })
-}
\ No newline at end of file
+    /// Resizes the file to exactly `size` bytes, padding with zeros if it's growing or
+    /// discarding trailing bytes if it's shrinking. Mirrors `FileSystemWritableFileStream`'s own
+    /// `truncate`.
+    async fn truncate(&mut self, size: usize) -> Result<(), Self::Error>;
+}
@@ -1,14 +1,53 @@
 //! "in-memory" filesystem for use in tests or when persistence isn't necessary
 
-use futures::Stream;
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use bytes::Bytes;
+use futures::{channel::mpsc, Stream};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    path::PathBuf,
+    rc::Rc,
+    time::SystemTime,
+};
+
+/// Chunk size used by [`FileHandle::read_stream`](crate::FileHandle::read_stream).
+const READ_STREAM_CHUNK_SIZE: usize = 64 * 1024;
 
 /// An entry in a virtual directory in the in-memory filesystem.
 pub type DirectoryEntry = crate::DirectoryEntry<DirectoryHandle, FileHandle>;
 
+/// The state shared by every clone of a [`DirectoryHandle`]: its entries plus whoever is
+/// currently watching it via [`crate::DirectoryHandle::watch_with_options`].
+struct DirectoryState {
+    entries: HashMap<String, DirectoryEntry>,
+    watchers: Vec<mpsc::UnboundedSender<crate::ChangeEvent>>,
+}
+
+impl std::fmt::Debug for DirectoryState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DirectoryState")
+            .field("entries", &self.entries)
+            .field("watcher_count", &self.watchers.len())
+            .finish()
+    }
+}
+
+/// Pushes `event` to every still-connected watcher of `state`, dropping senders whose receiver
+/// has gone away.
+fn notify(state: &Rc<RefCell<DirectoryState>>, kind: crate::ChangeKind, name: &str) {
+    let event = crate::ChangeEvent {
+        kind,
+        path: PathBuf::from(name),
+    };
+    state
+        .borrow_mut()
+        .watchers
+        .retain(|sender| sender.unbounded_send(event.clone()).is_ok());
+}
+
 /// A virtual directory in the in-memory filesystem.
 #[derive(Debug, Clone)]
-pub struct DirectoryHandle(Rc<RefCell<HashMap<String, DirectoryEntry>>>);
+pub struct DirectoryHandle(Rc<RefCell<DirectoryState>>);
 
 /// A virtual file in the in-memory filesystem.
 #[derive(Debug, Clone)]
@@ -19,6 +58,14 @@ pub struct FileHandle(WritableFileStream);
 pub struct WritableFileStream {
     cursor_pos: usize,
     stream: Rc<RefCell<Vec<u8>>>,
+    readonly: Rc<RefCell<bool>>,
+    created: Rc<RefCell<SystemTime>>,
+    modified: Rc<RefCell<SystemTime>>,
+    /// This file's own name within `parent`, used to label the `ChangeEvent`s emitted from
+    /// writes through this stream.
+    name: String,
+    /// The directory this file was handed out by, so writes can notify its watchers.
+    parent: Rc<RefCell<DirectoryState>>,
 }
 
 impl crate::private::Sealed for DirectoryHandle {}
@@ -34,20 +81,28 @@ impl crate::DirectoryHandle for DirectoryHandle {
         name: &str,
         options: &crate::GetFileHandleOptions,
     ) -> Result<Self::FileHandleT, Self::Error> {
-        let mut directory = self.0.borrow_mut();
-        let entry = match directory.entry(name.to_string()) {
-            std::collections::hash_map::Entry::Occupied(entry) => entry.get().clone(),
-            std::collections::hash_map::Entry::Vacant(entry) => {
-                if options.create {
-                    let file_handle = FileHandle::new();
-                    entry.insert(DirectoryEntry::File(file_handle.clone()));
-                    DirectoryEntry::File(file_handle)
-                } else {
-                    return Err(format!("'{name}' does not exist"));
+        let mut created = false;
+        let entry = {
+            let mut directory = self.0.borrow_mut();
+            match directory.entries.entry(name.to_string()) {
+                std::collections::hash_map::Entry::Occupied(entry) => entry.get().clone(),
+                std::collections::hash_map::Entry::Vacant(entry) => {
+                    if options.create {
+                        let file_handle = FileHandle::new(name.to_string(), self.0.clone());
+                        entry.insert(DirectoryEntry::File(file_handle.clone()));
+                        created = true;
+                        DirectoryEntry::File(file_handle)
+                    } else {
+                        return Err(format!("'{name}' does not exist"));
+                    }
                 }
             }
         };
 
+        if created {
+            notify(&self.0, crate::ChangeKind::Created, name);
+        }
+
         match entry {
             DirectoryEntry::Directory(_) => Err(format!("'{name}' is a directory")),
             DirectoryEntry::File(file) => Ok(file),
@@ -59,29 +114,113 @@ impl crate::DirectoryHandle for DirectoryHandle {
         name: &str,
         options: &crate::GetDirectoryHandleOptions,
     ) -> Result<Self, Self::Error> {
-        let mut directory = self.0.borrow_mut();
-        let entry = match directory.entry(name.to_string()) {
-            std::collections::hash_map::Entry::Occupied(entry) => entry.get().clone(),
-            std::collections::hash_map::Entry::Vacant(entry) => {
-                if options.create {
-                    let dir_handle = DirectoryHandle::default();
-                    entry.insert(DirectoryEntry::Directory(dir_handle.clone()));
-                    DirectoryEntry::Directory(dir_handle)
-                } else {
-                    return Err(format!("'{name}' does not exist"));
+        let mut created = false;
+        let entry = {
+            let mut directory = self.0.borrow_mut();
+            match directory.entries.entry(name.to_string()) {
+                std::collections::hash_map::Entry::Occupied(entry) => entry.get().clone(),
+                std::collections::hash_map::Entry::Vacant(entry) => {
+                    if options.create {
+                        let dir_handle = DirectoryHandle::default();
+                        entry.insert(DirectoryEntry::Directory(dir_handle.clone()));
+                        created = true;
+                        DirectoryEntry::Directory(dir_handle)
+                    } else {
+                        return Err(format!("'{name}' does not exist"));
+                    }
                 }
             }
         };
 
+        if created {
+            notify(&self.0, crate::ChangeKind::Created, name);
+        }
+
         match entry {
             DirectoryEntry::File(_) => Err(format!("'{name}' is a file")),
             DirectoryEntry::Directory(dir) => Ok(dir),
         }
     }
 
+    async fn metadata(&self) -> Result<crate::Metadata, Self::Error> {
+        Ok(crate::Metadata {
+            len: 0,
+            file_type: crate::FileType::Directory,
+            created: None,
+            modified: None,
+            accessed: None,
+            readonly: false,
+        })
+    }
+
+    async fn entry_metadata(&self, name: &str) -> Result<crate::Metadata, Self::Error> {
+        let entry = self
+            .0
+            .borrow()
+            .entries
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("'{name}' does not exist"))?;
+
+        match entry {
+            DirectoryEntry::File(file) => crate::FileHandle::metadata(&file).await,
+            DirectoryEntry::Directory(dir) => crate::DirectoryHandle::metadata(&dir).await,
+        }
+    }
+
+    async fn rename_entry(
+        &mut self,
+        from: &str,
+        to: &Self,
+        new_name: &str,
+    ) -> Result<(), Self::Error> {
+        let entry = self
+            .0
+            .borrow_mut()
+            .entries
+            .remove(from)
+            .ok_or_else(|| format!("'{from}' does not exist"))?;
+        to.0.borrow_mut().entries.insert(new_name.to_string(), entry);
+        Ok(())
+    }
+
+    async fn copy_entry(
+        &mut self,
+        name: &str,
+        dest: &Self,
+        new_name: &str,
+        options: &crate::CopyOptions,
+    ) -> Result<(), Self::Error> {
+        let entry = self
+            .0
+            .borrow()
+            .entries
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("'{name}' does not exist"))?;
+
+        if matches!(entry, DirectoryEntry::Directory(_)) && !options.recursive {
+            return Err(format!(
+                "'{name}' is a directory; set CopyOptions::recursive to copy it"
+            ));
+        }
+
+        if !options.overwrite && dest.0.borrow().entries.contains_key(new_name) {
+            return Err(format!("'{new_name}' already exists"));
+        }
+
+        dest.0.borrow_mut().entries.insert(
+            new_name.to_string(),
+            deep_clone_entry(&entry, new_name, &dest.0),
+        );
+        Ok(())
+    }
+
     async fn remove_entry(&mut self, name: &str) -> Result<(), Self::Error> {
-        let mut directory = self.0.borrow_mut();
-        directory.remove(name);
+        let removed = self.0.borrow_mut().entries.remove(name).is_some();
+        if removed {
+            notify(&self.0, crate::ChangeKind::Removed, name);
+        }
         Ok(())
     }
 
@@ -90,20 +229,24 @@ impl crate::DirectoryHandle for DirectoryHandle {
         name: &str,
         options: &crate::FileSystemRemoveOptions,
     ) -> Result<(), Self::Error> {
-        let mut directory = self.0.borrow_mut();
-        
-        if let Some(entry) = directory.get(name) {
-            match entry {
-                DirectoryEntry::Directory(dir) if !options.recursive => {
-                    if !dir.0.borrow().is_empty() {
-                        return Err(format!("Directory '{}' is not empty", name));
+        {
+            let directory = self.0.borrow();
+            if let Some(entry) = directory.entries.get(name) {
+                match entry {
+                    DirectoryEntry::Directory(dir) if !options.recursive => {
+                        if !dir.0.borrow().entries.is_empty() {
+                            return Err(format!("Directory '{}' is not empty", name));
+                        }
                     }
+                    _ => {}
                 }
-                _ => {}
             }
         }
-        
-        directory.remove(name);
+
+        let removed = self.0.borrow_mut().entries.remove(name).is_some();
+        if removed {
+            notify(&self.0, crate::ChangeKind::Removed, name);
+        }
         Ok(())
     }
 
@@ -113,18 +256,80 @@ impl crate::DirectoryHandle for DirectoryHandle {
     {
         let directory = self.0.borrow();
         let entries: Vec<_> = directory
+            .entries
             .iter()
             .map(|(name, entry)| Ok((name.clone(), entry.clone())))
             .collect();
         Ok(futures::stream::iter(entries))
     }
+
+    async fn watch_with_options(
+        &self,
+        _options: &crate::WatchOptions,
+    ) -> Result<impl Stream<Item = crate::ChangeEvent> + 'static, Self::Error> {
+        // `entries()` only ever reports this directory's immediate children, so a watch scopes
+        // the same way: subdirectories maintain their own independent subscriber list rather
+        // than bubbling events up to an ancestor.
+        let (sender, receiver) = mpsc::unbounded();
+        self.0.borrow_mut().watchers.push(sender);
+        Ok(receiver)
+    }
 }
 impl Default for DirectoryHandle {
     fn default() -> Self {
-        Self(Rc::new(RefCell::new(HashMap::new())))
+        Self(Rc::new(RefCell::new(DirectoryState {
+            entries: HashMap::new(),
+            watchers: Vec::new(),
+        })))
     }
 }
 
+/// Clones `entry` so the copy doesn't alias the original's `Rc`-backed storage; a write through
+/// one no longer shows up in the other. `name` and `parent` become the clone's own location, so
+/// that further writes through it notify the destination directory rather than the source.
+fn deep_clone_entry(
+    entry: &DirectoryEntry,
+    name: &str,
+    parent: &Rc<RefCell<DirectoryState>>,
+) -> DirectoryEntry {
+    match entry {
+        DirectoryEntry::File(file) => DirectoryEntry::File(deep_clone_file(file, name, parent)),
+        DirectoryEntry::Directory(dir) => DirectoryEntry::Directory(deep_clone_dir(dir)),
+    }
+}
+
+fn deep_clone_file(
+    file: &FileHandle,
+    name: &str,
+    parent: &Rc<RefCell<DirectoryState>>,
+) -> FileHandle {
+    FileHandle(WritableFileStream {
+        cursor_pos: 0,
+        stream: Rc::new(RefCell::new(file.0.stream.borrow().clone())),
+        readonly: Rc::new(RefCell::new(*file.0.readonly.borrow())),
+        created: Rc::new(RefCell::new(*file.0.created.borrow())),
+        modified: Rc::new(RefCell::new(*file.0.modified.borrow())),
+        name: name.to_string(),
+        parent: parent.clone(),
+    })
+}
+
+fn deep_clone_dir(dir: &DirectoryHandle) -> DirectoryHandle {
+    let cloned_state = Rc::new(RefCell::new(DirectoryState {
+        entries: HashMap::new(),
+        watchers: Vec::new(),
+    }));
+    let cloned_entries: HashMap<String, DirectoryEntry> = dir
+        .0
+        .borrow()
+        .entries
+        .iter()
+        .map(|(name, entry)| (name.clone(), deep_clone_entry(entry, name, &cloned_state)))
+        .collect();
+    cloned_state.borrow_mut().entries = cloned_entries;
+    DirectoryHandle(cloned_state)
+}
+
 impl crate::FileHandle for FileHandle {
     type Error = String;
     type WritableFileStreamT = WritableFileStream;
@@ -133,6 +338,9 @@ impl crate::FileHandle for FileHandle {
         &mut self,
         options: &crate::CreateWritableOptions,
     ) -> Result<Self::WritableFileStreamT, Self::Error> {
+        if *self.0.readonly.borrow() {
+            return Err("cannot open a readonly file for writing".to_string());
+        }
         if !options.keep_existing_data {
             self.0.stream.borrow_mut().clear();
         }
@@ -142,37 +350,75 @@ impl crate::FileHandle for FileHandle {
         })
     }
 
-    async fn read(&self) -> Result<Vec<u8>, Self::Error> {
-        let stream = self.0.stream.clone();
-        let data = stream.borrow().clone();
-        Ok(data)
-    }
-
     async fn size(&self) -> Result<usize, Self::Error> {
         Ok(self.0.len())
     }
+
+    async fn read_range(&self, offset: usize, len: usize) -> Result<Vec<u8>, Self::Error> {
+        let stream = self.0.stream.borrow();
+        if offset >= stream.len() {
+            return Ok(Vec::new());
+        }
+        let end = (offset + len).min(stream.len());
+        Ok(stream[offset..end].to_vec())
+    }
+
+    async fn read_stream(
+        &self,
+    ) -> Result<impl Stream<Item = Result<Bytes, Self::Error>>, Self::Error> {
+        let data = self.0.stream.borrow().clone();
+        let chunks: Vec<_> = data
+            .chunks(READ_STREAM_CHUNK_SIZE)
+            .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+            .collect();
+        Ok(futures::stream::iter(chunks))
+    }
+
+    async fn metadata(&self) -> Result<crate::Metadata, Self::Error> {
+        Ok(crate::Metadata {
+            len: self.0.len() as u64,
+            file_type: crate::FileType::File,
+            created: Some(*self.0.created.borrow()),
+            modified: Some(*self.0.modified.borrow()),
+            accessed: None,
+            readonly: *self.0.readonly.borrow(),
+        })
+    }
+
+    async fn set_readonly(&mut self, readonly: bool) -> Result<(), Self::Error> {
+        *self.0.readonly.borrow_mut() = readonly;
+        Ok(())
+    }
 }
 
 impl crate::WritableFileStream for WritableFileStream {
     type Error = String;
 
     async fn write_at_cursor_pos(&mut self, data: Vec<u8>) -> Result<(), Self::Error> {
-        let data_len = data.len();
+        if *self.readonly.borrow() {
+            return Err("cannot write to a readonly file".to_string());
+        }
 
-        let mut stream = self.stream.borrow_mut();
-        *stream = stream[0..self.cursor_pos]
-            .iter()
-            .cloned()
-            .chain(data)
-            .collect::<Vec<u8>>();
+        let end = self.cursor_pos + data.len();
 
-        self.cursor_pos += data_len;
+        {
+            let mut stream = self.stream.borrow_mut();
+            if end > stream.len() {
+                stream.resize(end, 0);
+            }
+            stream[self.cursor_pos..end].copy_from_slice(&data);
+        }
+
+        self.cursor_pos = end;
+        *self.modified.borrow_mut() = SystemTime::now();
+        notify(&self.parent, crate::ChangeKind::Modified, &self.name);
 
         Ok(())
     }
 
     async fn close(&mut self) -> Result<(), Self::Error> {
-        // no op
+        *self.modified.borrow_mut() = SystemTime::now();
+        notify(&self.parent, crate::ChangeKind::Modified, &self.name);
         Ok(())
     }
 
@@ -186,19 +432,32 @@ impl crate::WritableFileStream for WritableFileStream {
         self.cursor_pos = offset;
         Ok(())
     }
+
+    async fn truncate(&mut self, size: usize) -> Result<(), Self::Error> {
+        self.stream.borrow_mut().resize(size, 0);
+        self.cursor_pos = self.cursor_pos.min(size);
+        *self.modified.borrow_mut() = SystemTime::now();
+        Ok(())
+    }
 }
 
 impl FileHandle {
-    fn new() -> Self {
-        Self(WritableFileStream::new())
+    fn new(name: String, parent: Rc<RefCell<DirectoryState>>) -> Self {
+        Self(WritableFileStream::new(name, parent))
     }
 }
 
 impl WritableFileStream {
-    fn new() -> Self {
+    fn new(name: String, parent: Rc<RefCell<DirectoryState>>) -> Self {
+        let now = SystemTime::now();
         Self {
             cursor_pos: 0,
             stream: Rc::new(RefCell::new(Vec::new())),
+            readonly: Rc::new(RefCell::new(false)),
+            created: Rc::new(RefCell::new(now)),
+            modified: Rc::new(RefCell::new(now)),
+            name,
+            parent,
         }
     }
 
@@ -228,6 +487,7 @@ mod tests {
 
         let write_options = CreateWritableOptions {
             keep_existing_data: false,
+            atomic: false,
         };
         let mut writer = file
             .create_writable_with_options(&write_options)
@@ -317,6 +577,7 @@ mod tests {
 
         let write_options = CreateWritableOptions {
             keep_existing_data: false,
+            atomic: false,
         };
         let mut writer = file
             .create_writable_with_options(&write_options)
@@ -329,7 +590,7 @@ mod tests {
         writer.close().await.unwrap();
 
         let data = file.read().await.unwrap();
-        assert_eq!(data, b"Hi");
+        assert_eq!(data, b"Hillo"); // "Hi" overwrites the first 2 bytes; the rest is preserved
     }
 
     #[tokio::test]
@@ -344,6 +605,7 @@ mod tests {
 
         let write_options = CreateWritableOptions {
             keep_existing_data: false,
+            atomic: false,
         };
         let mut writer = file
             .create_writable_with_options(&write_options)
@@ -369,6 +631,7 @@ mod tests {
 
         let write_options = CreateWritableOptions {
             keep_existing_data: false,
+            atomic: false,
         };
         let mut writer = file
             .create_writable_with_options(&write_options)
@@ -379,6 +642,7 @@ mod tests {
 
         let keep_options = CreateWritableOptions {
             keep_existing_data: true,
+            atomic: false,
         };
         let mut writer2 = file
             .create_writable_with_options(&keep_options)
@@ -393,4 +657,325 @@ mod tests {
         let data = file.read().await.unwrap();
         assert_eq!(data, b" World");
     }
+
+    #[tokio::test]
+    async fn test_read_range_clamps_to_remaining_bytes() {
+        let dir = DirectoryHandle::default();
+        let options = GetFileHandleOptions { create: true };
+
+        let mut file = dir
+            .get_file_handle_with_options("test.txt", &options)
+            .await
+            .unwrap();
+        let mut writer = file.create_writable().await.unwrap();
+        writer.write_at_cursor_pos(b"Hello, world!".to_vec()).await.unwrap();
+        writer.close().await.unwrap();
+
+        assert_eq!(file.read_range(7, 5).await.unwrap(), b"world");
+        assert_eq!(file.read_range(7, 100).await.unwrap(), b"world!");
+        assert_eq!(file.read_range(100, 5).await.unwrap(), b"");
+    }
+
+    #[tokio::test]
+    async fn test_read_stream_reassembles_into_full_contents() {
+        let dir = DirectoryHandle::default();
+        let options = GetFileHandleOptions { create: true };
+
+        let mut file = dir
+            .get_file_handle_with_options("test.txt", &options)
+            .await
+            .unwrap();
+        let data = vec![7u8; READ_STREAM_CHUNK_SIZE * 2 + 1];
+        let mut writer = file.create_writable().await.unwrap();
+        writer.write_at_cursor_pos(data.clone()).await.unwrap();
+        writer.close().await.unwrap();
+
+        let stream = file.read_stream().await.unwrap();
+        let chunks: Vec<_> = stream.collect::<Vec<_>>().await;
+
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(file.read().await.unwrap(), data);
+    }
+
+    #[tokio::test]
+    async fn test_rename_entry_moves_file_between_directories() {
+        let mut src_dir = DirectoryHandle::default();
+        let dest_dir = DirectoryHandle::default();
+        let options = GetFileHandleOptions { create: true };
+
+        let mut file = src_dir
+            .get_file_handle_with_options("a.txt", &options)
+            .await
+            .unwrap();
+        let mut writer = file.create_writable().await.unwrap();
+        writer.write_at_cursor_pos(b"hello".to_vec()).await.unwrap();
+        writer.close().await.unwrap();
+
+        crate::DirectoryHandle::rename_entry(&mut src_dir, "a.txt", &dest_dir, "b.txt")
+            .await
+            .unwrap();
+
+        assert!(
+            src_dir
+                .get_file_handle_with_options("a.txt", &GetFileHandleOptions { create: false })
+                .await
+                .is_err()
+        );
+        let moved = dest_dir
+            .get_file_handle_with_options("b.txt", &GetFileHandleOptions { create: false })
+            .await
+            .unwrap();
+        assert_eq!(moved.read().await.unwrap(), b"hello");
+    }
+
+    #[tokio::test]
+    async fn test_copy_entry_duplicates_file_contents_independently() {
+        let mut src_dir = DirectoryHandle::default();
+        let dest_dir = DirectoryHandle::default();
+        let options = GetFileHandleOptions { create: true };
+
+        let mut file = src_dir
+            .get_file_handle_with_options("a.txt", &options)
+            .await
+            .unwrap();
+        let mut writer = file.create_writable().await.unwrap();
+        writer.write_at_cursor_pos(b"hello".to_vec()).await.unwrap();
+        writer.close().await.unwrap();
+
+        crate::DirectoryHandle::copy_entry(
+            &mut src_dir,
+            "a.txt",
+            &dest_dir,
+            "b.txt",
+            &crate::CopyOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        let mut copied = dest_dir
+            .get_file_handle_with_options("b.txt", &GetFileHandleOptions { create: false })
+            .await
+            .unwrap();
+        assert_eq!(copied.read().await.unwrap(), b"hello");
+
+        let mut writer = copied.create_writable().await.unwrap();
+        writer.write_at_cursor_pos(b"changed".to_vec()).await.unwrap();
+        writer.close().await.unwrap();
+
+        assert_eq!(file.read().await.unwrap(), b"hello");
+    }
+
+    #[tokio::test]
+    async fn test_copy_entry_directory_requires_recursive() {
+        let mut src_dir = DirectoryHandle::default();
+        let dest_dir = DirectoryHandle::default();
+
+        let _child = src_dir
+            .get_directory_handle_with_options(
+                "child",
+                &crate::GetDirectoryHandleOptions { create: true },
+            )
+            .await
+            .unwrap();
+
+        let result = crate::DirectoryHandle::copy_entry(
+            &mut src_dir,
+            "child",
+            &dest_dir,
+            "child",
+            &crate::CopyOptions::default(),
+        )
+        .await;
+        assert!(result.is_err());
+
+        crate::DirectoryHandle::copy_entry(
+            &mut src_dir,
+            "child",
+            &dest_dir,
+            "child",
+            &crate::CopyOptions {
+                recursive: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert!(
+            dest_dir
+                .get_directory_handle_with_options(
+                    "child",
+                    &crate::GetDirectoryHandleOptions { create: false },
+                )
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_metadata_reports_size_and_type() {
+        let dir = DirectoryHandle::default();
+        let options = GetFileHandleOptions { create: true };
+
+        let mut file = dir
+            .get_file_handle_with_options("test.txt", &options)
+            .await
+            .unwrap();
+
+        let mut writer = file.create_writable().await.unwrap();
+        writer.write_at_cursor_pos(b"Hello".to_vec()).await.unwrap();
+        writer.close().await.unwrap();
+
+        let metadata = file.metadata().await.unwrap();
+        assert_eq!(metadata.len, 5);
+        assert_eq!(metadata.file_type, crate::FileType::File);
+        assert!(metadata.file_type.is_file());
+        assert!(!metadata.file_type.is_dir());
+        assert!(!metadata.readonly);
+
+        let dir_metadata = dir.metadata().await.unwrap();
+        assert_eq!(dir_metadata.file_type, crate::FileType::Directory);
+        assert!(dir_metadata.file_type.is_dir());
+        assert!(!dir_metadata.file_type.is_file());
+    }
+
+    #[tokio::test]
+    async fn test_set_readonly_blocks_writes() {
+        let dir = DirectoryHandle::default();
+        let options = GetFileHandleOptions { create: true };
+
+        let mut file = dir
+            .get_file_handle_with_options("test.txt", &options)
+            .await
+            .unwrap();
+
+        file.set_readonly(true).await.unwrap();
+        assert!(file.metadata().await.unwrap().readonly);
+
+        assert!(file.create_writable().await.is_err());
+
+        file.set_readonly(false).await.unwrap();
+        let mut writer = file.create_writable().await.unwrap();
+        writer.write_at_cursor_pos(b"ok".to_vec()).await.unwrap();
+        writer.close().await.unwrap();
+        assert_eq!(file.read().await.unwrap(), b"ok");
+    }
+
+    #[tokio::test]
+    async fn test_create_writable_on_readonly_file_does_not_clear_its_contents() {
+        let dir = DirectoryHandle::default();
+        let options = GetFileHandleOptions { create: true };
+
+        let mut file = dir
+            .get_file_handle_with_options("test.txt", &options)
+            .await
+            .unwrap();
+        let mut writer = file.create_writable().await.unwrap();
+        writer.write_at_cursor_pos(b"original".to_vec()).await.unwrap();
+        writer.close().await.unwrap();
+
+        file.set_readonly(true).await.unwrap();
+        assert!(file.create_writable().await.is_err());
+
+        // The failed open must not have cleared the existing contents.
+        assert_eq!(file.read().await.unwrap(), b"original");
+    }
+
+    #[tokio::test]
+    async fn test_truncate_grows_and_shrinks_file() {
+        let dir = DirectoryHandle::default();
+        let options = GetFileHandleOptions { create: true };
+
+        let mut file = dir
+            .get_file_handle_with_options("test.txt", &options)
+            .await
+            .unwrap();
+        let mut writer = file.create_writable().await.unwrap();
+        writer.write_at_cursor_pos(b"Hello, world!".to_vec()).await.unwrap();
+
+        writer.truncate(5).await.unwrap();
+        writer.close().await.unwrap();
+        assert_eq!(file.read().await.unwrap(), b"Hello");
+
+        let mut writer = file.create_writable_with_options(&crate::CreateWritableOptions {
+            keep_existing_data: true,
+            atomic: false,
+        }).await.unwrap();
+        writer.truncate(8).await.unwrap();
+        writer.close().await.unwrap();
+        assert_eq!(file.read().await.unwrap(), b"Hello\0\0\0");
+    }
+
+    #[tokio::test]
+    async fn test_watch_reports_create_modify_and_remove() {
+        let dir = DirectoryHandle::default();
+        let mut events = dir
+            .watch_with_options(&crate::WatchOptions::default())
+            .await
+            .unwrap();
+
+        let options = GetFileHandleOptions { create: true };
+        let mut file = dir
+            .get_file_handle_with_options("test.txt", &options)
+            .await
+            .unwrap();
+
+        let created = events.next().await.unwrap();
+        assert_eq!(created.kind, crate::ChangeKind::Created);
+        assert_eq!(created.path, std::path::PathBuf::from("test.txt"));
+
+        let mut writer = file.create_writable().await.unwrap();
+        writer.write_at_cursor_pos(b"hi".to_vec()).await.unwrap();
+        writer.close().await.unwrap();
+
+        let modified = events.next().await.unwrap();
+        assert_eq!(modified.kind, crate::ChangeKind::Modified);
+        let modified = events.next().await.unwrap();
+        assert_eq!(modified.kind, crate::ChangeKind::Modified);
+
+        let mut dir = dir;
+        crate::DirectoryHandle::remove_entry(&mut dir, "test.txt")
+            .await
+            .unwrap();
+
+        let removed = events.next().await.unwrap();
+        assert_eq!(removed.kind, crate::ChangeKind::Removed);
+        assert_eq!(removed.path, std::path::PathBuf::from("test.txt"));
+    }
+
+    #[tokio::test]
+    async fn test_entry_metadata_stats_files_and_directories_without_a_handle() {
+        let dir = DirectoryHandle::default();
+
+        let mut file = dir
+            .get_file_handle_with_options("test.txt", &GetFileHandleOptions { create: true })
+            .await
+            .unwrap();
+        let mut writer = file.create_writable().await.unwrap();
+        writer.write_at_cursor_pos(b"hello".to_vec()).await.unwrap();
+        writer.close().await.unwrap();
+
+        let file_meta = crate::DirectoryHandle::entry_metadata(&dir, "test.txt")
+            .await
+            .unwrap();
+        assert_eq!(file_meta.len, 5);
+        assert_eq!(file_meta.file_type, crate::FileType::File);
+        assert!(file_meta.modified.is_some());
+
+        dir.get_directory_handle_with_options(
+            "sub",
+            &crate::GetDirectoryHandleOptions { create: true },
+        )
+        .await
+        .unwrap();
+        let dir_meta = crate::DirectoryHandle::entry_metadata(&dir, "sub")
+            .await
+            .unwrap();
+        assert_eq!(dir_meta.file_type, crate::FileType::Directory);
+
+        assert!(
+            crate::DirectoryHandle::entry_metadata(&dir, "missing.txt")
+                .await
+                .is_err()
+        );
+    }
 }
@@ -1,9 +1,14 @@
+use bytes::Bytes;
 use futures::Stream;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::{io::SeekFrom, path::PathBuf};
-use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
 use tokio::sync::RwLock;
 
+/// Chunk size used by [`FileHandle::read_stream`](crate::FileHandle::read_stream).
+const READ_STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
 type DirectoryEntry = crate::DirectoryEntry<DirectoryHandle, FileHandle>;
 
 #[derive(Clone, Debug)]
@@ -13,7 +18,196 @@ pub struct DirectoryHandle(PathBuf);
 pub struct FileHandle(PathBuf);
 
 #[derive(Clone, Debug)]
-pub struct WritableFileStream(Arc<RwLock<tokio::fs::File>>);
+pub struct WritableFileStream(Arc<RwLock<WritableFileStreamInner>>);
+
+/// Bookkeeping for a write that lands in a sibling temp file and is only swapped into place
+/// on `close()`. See [`FileHandle::create_writable_with_options`] and
+/// [`CreateWritableOptions::atomic`](crate::CreateWritableOptions::atomic).
+#[derive(Debug)]
+struct AtomicWrite {
+    tmp_path: PathBuf,
+    dest_path: PathBuf,
+    closed: bool,
+}
+
+#[derive(Debug)]
+struct WritableFileStreamInner {
+    file: tokio::fs::File,
+    atomic: Option<AtomicWrite>,
+}
+
+impl Drop for WritableFileStreamInner {
+    fn drop(&mut self) {
+        if let Some(atomic) = &self.atomic {
+            if !atomic.closed {
+                let _ = std::fs::remove_file(&atomic.tmp_path);
+            }
+        }
+    }
+}
+
+/// Builds a `.<name>.<unique>.tmp` path next to `dest` for an atomic write.
+fn temp_sibling_path(dest: &std::path::Path) -> PathBuf {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let name = dest.file_name().and_then(|n| n.to_str()).unwrap_or("file");
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let unique = COUNTER.fetch_add(1, Ordering::Relaxed);
+
+    dest.with_file_name(format!(".{name}.{}-{nanos}-{unique}.tmp", std::process::id()))
+}
+
+/// `rename` only moves atomically within a single filesystem; a cross-device temp directory
+/// (e.g. `/tmp` on a different mount than the destination) surfaces this as `EXDEV`.
+fn is_cross_device_error(error: &std::io::Error) -> bool {
+    matches!(error.raw_os_error(), Some(18))
+}
+
+/// Iteratively copies `src` into `dest`, both directories, creating `dest` (and any
+/// subdirectories) as needed. Uses an explicit work queue rather than `async fn` recursion, as
+/// with [`crate::walk::walk`].
+async fn copy_dir_recursive(src: &PathBuf, dest: &PathBuf, overwrite: bool) -> std::io::Result<()> {
+    let mut queue = std::collections::VecDeque::new();
+    queue.push_back((src.clone(), dest.clone()));
+
+    while let Some((src_dir, dest_dir)) = queue.pop_front() {
+        tokio::fs::create_dir_all(&dest_dir).await?;
+
+        let mut read_dir = tokio::fs::read_dir(&src_dir).await?;
+        while let Some(entry) = read_dir.next_entry().await? {
+            let entry_path = entry.path();
+            let dest_path = dest_dir.join(entry.file_name());
+            let file_type = entry.file_type().await?;
+
+            if file_type.is_dir() {
+                queue.push_back((entry_path, dest_path));
+            } else {
+                if !overwrite && dest_path.exists() {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::AlreadyExists,
+                        format!("'{}' already exists", dest_path.display()),
+                    ));
+                }
+                tokio::fs::copy(&entry_path, &dest_path).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Converts a `std::fs::Metadata` into the crate's backend-agnostic [`crate::Metadata`].
+fn metadata_from_std(metadata: std::fs::Metadata) -> crate::Metadata {
+    let file_type = if metadata.file_type().is_symlink() {
+        crate::FileType::Symlink
+    } else if metadata.is_dir() {
+        crate::FileType::Directory
+    } else {
+        crate::FileType::File
+    };
+
+    crate::Metadata {
+        len: metadata.len(),
+        file_type,
+        created: metadata.created().ok(),
+        modified: metadata.modified().ok(),
+        accessed: metadata.accessed().ok(),
+        readonly: metadata.permissions().readonly(),
+    }
+}
+
+/// How long a path's change events are held back waiting for more of the same before being
+/// emitted, so a burst of writes to one file surfaces as a single coalesced event.
+const WATCH_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(50);
+
+fn classify_event_kind(kind: &notify::EventKind) -> Option<crate::ChangeKind> {
+    use notify::event::{EventKind, ModifyKind, RenameMode};
+
+    match kind {
+        EventKind::Create(_) => Some(crate::ChangeKind::Created),
+        EventKind::Remove(_) => Some(crate::ChangeKind::Removed),
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both | RenameMode::From | RenameMode::To)) => {
+            Some(crate::ChangeKind::Renamed)
+        }
+        EventKind::Modify(_) => Some(crate::ChangeKind::Modified),
+        EventKind::Access(_) | EventKind::Any | EventKind::Other => None,
+    }
+}
+
+/// Watches `root`, bridging `notify`'s callback-based events onto a debounced async stream.
+/// The watcher is kept alive for as long as the returned stream is, so dropping the stream
+/// tears it down.
+fn start_watch(
+    root: PathBuf,
+    options: &crate::WatchOptions,
+) -> std::io::Result<impl Stream<Item = crate::ChangeEvent> + 'static> {
+    use notify::Watcher;
+
+    let recursive_mode = if options.recursive {
+        notify::RecursiveMode::Recursive
+    } else {
+        notify::RecursiveMode::NonRecursive
+    };
+
+    let (raw_tx, raw_rx) = std::sync::mpsc::channel();
+    let mut watcher =
+        notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let _ = raw_tx.send(event);
+        })
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+    watcher
+        .watch(&root, recursive_mode)
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+
+    let (events_tx, events_rx) = tokio::sync::mpsc::unbounded_channel();
+
+    tokio::task::spawn_blocking(move || {
+        let mut pending: std::collections::HashMap<PathBuf, (crate::ChangeKind, std::time::Instant)> =
+            std::collections::HashMap::new();
+
+        loop {
+            match raw_rx.recv_timeout(WATCH_DEBOUNCE) {
+                Ok(Ok(event)) => {
+                    if let Some(kind) = classify_event_kind(&event.kind) {
+                        for path in event.paths {
+                            let relative = path.strip_prefix(&root).unwrap_or(&path).to_path_buf();
+                            pending.insert(relative, (kind, std::time::Instant::now()));
+                        }
+                    }
+                }
+                Ok(Err(_)) => {}
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {}
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+
+            let now = std::time::Instant::now();
+            let ready: Vec<_> = pending
+                .iter()
+                .filter(|(_, (_, seen_at))| now.duration_since(*seen_at) >= WATCH_DEBOUNCE)
+                .map(|(path, _)| path.clone())
+                .collect();
+
+            for path in ready {
+                if let Some((kind, _)) = pending.remove(&path) {
+                    if events_tx.send(crate::ChangeEvent { kind, path }).is_err() {
+                        return;
+                    }
+                }
+            }
+        }
+
+        for (path, (kind, _)) in pending {
+            let _ = events_tx.send(crate::ChangeEvent { kind, path });
+        }
+    });
+
+    Ok(futures::stream::unfold((watcher, events_rx), |(watcher, mut rx)| async move {
+        rx.recv().await.map(|event| (event, (watcher, rx)))
+    }))
+}
 
 impl From<PathBuf> for DirectoryHandle {
     fn from(handle: PathBuf) -> Self {
@@ -29,7 +223,10 @@ impl From<PathBuf> for FileHandle {
 
 impl From<tokio::fs::File> for WritableFileStream {
     fn from(handle: tokio::fs::File) -> Self {
-        Self(Arc::new(RwLock::new(handle)))
+        Self(Arc::new(RwLock::new(WritableFileStreamInner {
+            file: handle,
+            atomic: None,
+        })))
     }
 }
 
@@ -80,6 +277,63 @@ impl crate::DirectoryHandle for DirectoryHandle {
         Ok(DirectoryHandle(path))
     }
 
+    async fn metadata(&self) -> Result<crate::Metadata, Self::Error> {
+        Ok(metadata_from_std(tokio::fs::symlink_metadata(&self.0).await?))
+    }
+
+    async fn entry_metadata(&self, name: &str) -> Result<crate::Metadata, Self::Error> {
+        let mut path = self.0.clone();
+        path.push(name);
+        Ok(metadata_from_std(tokio::fs::symlink_metadata(&path).await?))
+    }
+
+    async fn rename_entry(
+        &mut self,
+        from: &str,
+        to: &Self,
+        new_name: &str,
+    ) -> Result<(), Self::Error> {
+        let mut src = self.0.clone();
+        src.push(from);
+        let mut dest = to.0.clone();
+        dest.push(new_name);
+        tokio::fs::rename(&src, &dest).await
+    }
+
+    async fn copy_entry(
+        &mut self,
+        name: &str,
+        dest: &Self,
+        new_name: &str,
+        options: &crate::CopyOptions,
+    ) -> Result<(), Self::Error> {
+        let mut src = self.0.clone();
+        src.push(name);
+        let mut dest_path = dest.0.clone();
+        dest_path.push(new_name);
+
+        let metadata = tokio::fs::metadata(&src).await?;
+        if metadata.is_dir() {
+            if !options.recursive {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("'{name}' is a directory; set CopyOptions::recursive to copy it"),
+                ));
+            }
+            copy_dir_recursive(&src, &dest_path, options.overwrite).await?;
+        } else {
+            if !options.overwrite && dest_path.exists() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::AlreadyExists,
+                    format!("'{}' already exists", dest_path.display()),
+                ));
+            }
+            tokio::fs::copy(&src, &dest_path).await?;
+        }
+
+        Ok(())
+    }
+
     async fn remove_entry(&mut self, name: &str) -> Result<(), Self::Error> {
         let mut path = self.0.clone();
         path.push(name);
@@ -103,14 +357,23 @@ impl crate::DirectoryHandle for DirectoryHandle {
 
         while let Some(entry) = read_dir.next_entry().await? {
             let name = entry.file_name().to_string_lossy().to_string();
-            let metadata = entry.metadata().await?;
+            // `DirEntry::file_type` doesn't follow symlinks, unlike `DirEntry::metadata`; a
+            // symlink needs its target's type to decide which handle variant it becomes, while
+            // the handle's own path stays the symlink so `metadata()` can still report it as one.
+            let file_type = entry.file_type().await?;
 
-            let dir_entry = if metadata.is_file() {
-                DirectoryEntry::File(FileHandle(entry.path()))
-            } else if metadata.is_dir() {
+            let points_to_dir = if file_type.is_symlink() {
+                tokio::fs::metadata(entry.path()).await.map(|m| m.is_dir()).unwrap_or(false)
+            } else {
+                file_type.is_dir()
+            };
+
+            let dir_entry = if points_to_dir {
                 DirectoryEntry::Directory(DirectoryHandle(entry.path()))
+            } else if file_type.is_file() || file_type.is_symlink() {
+                DirectoryEntry::File(FileHandle(entry.path()))
             } else {
-                continue; // Skip other types like symlinks
+                continue; // Skip other types like sockets and FIFOs
             };
 
             entries.push(Ok((name, dir_entry)));
@@ -118,6 +381,13 @@ impl crate::DirectoryHandle for DirectoryHandle {
 
         Ok(futures::stream::iter(entries))
     }
+
+    async fn watch_with_options(
+        &self,
+        options: &crate::WatchOptions,
+    ) -> Result<impl Stream<Item = crate::ChangeEvent> + 'static, Self::Error> {
+        start_watch(self.0.clone(), options)
+    }
 }
 
 impl crate::FileHandle for FileHandle {
@@ -128,6 +398,29 @@ impl crate::FileHandle for FileHandle {
         &mut self,
         options: &crate::CreateWritableOptions,
     ) -> Result<Self::WritableFileStreamT, Self::Error> {
+        if options.atomic {
+            let tmp_path = temp_sibling_path(&self.0);
+            let mut tmp_file = tokio::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&tmp_path)
+                .await?;
+
+            if options.keep_existing_data {
+                let existing = tokio::fs::read(&self.0).await?;
+                tmp_file.write_all(&existing).await?;
+            }
+
+            return Ok(WritableFileStream(Arc::new(RwLock::new(WritableFileStreamInner {
+                file: tmp_file,
+                atomic: Some(AtomicWrite {
+                    tmp_path,
+                    dest_path: self.0.clone(),
+                    closed: false,
+                }),
+            }))));
+        }
+
         let file = tokio::fs::OpenOptions::new()
             .write(true)
             .create(true)
@@ -135,21 +428,56 @@ impl crate::FileHandle for FileHandle {
             .open(&self.0)
             .await?;
 
-        Ok(WritableFileStream(Arc::new(RwLock::new(file))))
+        Ok(WritableFileStream(Arc::new(RwLock::new(WritableFileStreamInner {
+            file,
+            atomic: None,
+        }))))
     }
 
-    async fn read(&self) -> Result<Vec<u8>, Self::Error> {
-        use tokio::io::AsyncReadExt;
+    async fn size(&self) -> Result<usize, Self::Error> {
+        let metadata = tokio::fs::metadata(&self.0).await?;
+        Ok(metadata.len() as usize)
+    }
+
+    async fn read_range(&self, offset: usize, len: usize) -> Result<Vec<u8>, Self::Error> {
+        let file_len = tokio::fs::metadata(&self.0).await?.len() as usize;
+        if offset >= file_len {
+            return Ok(Vec::new());
+        }
 
+        let clamped_len = len.min(file_len - offset);
         let mut file = tokio::fs::File::open(&self.0).await?;
-        let mut buffer = Vec::new();
-        file.read_to_end(&mut buffer).await?;
+        file.seek(SeekFrom::Start(offset as u64)).await?;
+
+        let mut buffer = vec![0u8; clamped_len];
+        file.read_exact(&mut buffer).await?;
         Ok(buffer)
     }
 
-    async fn size(&self) -> Result<usize, Self::Error> {
-        let metadata = tokio::fs::metadata(&self.0).await?;
-        Ok(metadata.len() as usize)
+    async fn read_stream(
+        &self,
+    ) -> Result<impl Stream<Item = Result<Bytes, Self::Error>>, Self::Error> {
+        let file = tokio::fs::File::open(&self.0).await?;
+
+        Ok(futures::stream::try_unfold(file, |mut file| async move {
+            let mut buffer = vec![0u8; READ_STREAM_CHUNK_SIZE];
+            let read = file.read(&mut buffer).await?;
+            if read == 0 {
+                return Ok(None);
+            }
+            buffer.truncate(read);
+            Ok(Some((Bytes::from(buffer), file)))
+        }))
+    }
+
+    async fn metadata(&self) -> Result<crate::Metadata, Self::Error> {
+        Ok(metadata_from_std(tokio::fs::symlink_metadata(&self.0).await?))
+    }
+
+    async fn set_readonly(&mut self, readonly: bool) -> Result<(), Self::Error> {
+        let mut permissions = tokio::fs::metadata(&self.0).await?.permissions();
+        permissions.set_readonly(readonly);
+        tokio::fs::set_permissions(&self.0, permissions).await
     }
 }
 
@@ -157,20 +485,71 @@ impl crate::WritableFileStream for WritableFileStream {
     type Error = std::io::Error;
 
     async fn write_at_cursor_pos(&mut self, data: Vec<u8>) -> Result<(), Self::Error> {
-        let mut file = self.0.write().await;
-        file.write_all(&data).await?;
+        let mut inner = self.0.write().await;
+        inner.file.write_all(&data).await?;
         Ok(())
     }
 
     async fn close(&mut self) -> Result<(), Self::Error> {
-        let mut file = self.0.write().await;
-        file.shutdown().await?;
-        Ok(())
+        let mut inner = self.0.write().await;
+        inner.file.shutdown().await?;
+
+        let Some(mut atomic) = inner.atomic.take() else {
+            return Ok(());
+        };
+
+        let result = finalize_atomic_write(&mut inner.file, &atomic).await;
+        atomic.closed = result.is_ok();
+        inner.atomic = Some(atomic);
+        result
     }
 
     async fn seek(&mut self, offset: usize) -> Result<(), Self::Error> {
-        let mut file = self.0.write().await;
-        file.seek(SeekFrom::Start(offset as u64)).await?;
+        let mut inner = self.0.write().await;
+        inner.file.seek(SeekFrom::Start(offset as u64)).await?;
+        Ok(())
+    }
+
+    async fn truncate(&mut self, size: usize) -> Result<(), Self::Error> {
+        let mut inner = self.0.write().await;
+        inner.file.set_len(size as u64).await
+    }
+}
+
+/// Fsyncs `file`, swaps the temp file into place, and best-effort fsyncs the parent
+/// directory so the rename survives a power loss.
+async fn finalize_atomic_write(file: &mut tokio::fs::File, atomic: &AtomicWrite) -> std::io::Result<()> {
+    file.sync_all().await?;
+
+    match tokio::fs::rename(&atomic.tmp_path, &atomic.dest_path).await {
+        Ok(()) => {}
+        Err(e) if is_cross_device_error(&e) => {
+            tokio::fs::copy(&atomic.tmp_path, &atomic.dest_path).await?;
+            tokio::fs::remove_file(&atomic.tmp_path).await?;
+        }
+        Err(e) => return Err(e),
+    }
+
+    if let Some(parent) = atomic.dest_path.parent() {
+        if let Ok(dir) = tokio::fs::File::open(parent).await {
+            let _ = dir.sync_all().await;
+        }
+    }
+
+    Ok(())
+}
+
+impl WritableFileStream {
+    /// Aborts an in-progress atomic write, discarding the temporary file instead of swapping
+    /// it into place. No-op for streams created without [`CreateWritableOptions::atomic`](crate::CreateWritableOptions::atomic).
+    pub async fn abort(&mut self) -> std::io::Result<()> {
+        let mut inner = self.0.write().await;
+        if let Some(atomic) = inner.atomic.as_mut() {
+            if !atomic.closed {
+                let _ = tokio::fs::remove_file(&atomic.tmp_path).await;
+                atomic.closed = true;
+            }
+        }
         Ok(())
     }
 }
@@ -203,6 +582,7 @@ mod tests {
 
         let write_options = CreateWritableOptions {
             keep_existing_data: false,
+            atomic: false,
         };
         let mut writer = file
             .create_writable_with_options(&write_options)
@@ -328,6 +708,7 @@ mod tests {
 
         let write_options = CreateWritableOptions {
             keep_existing_data: false,
+            atomic: false,
         };
         let mut writer = file
             .create_writable_with_options(&write_options)
@@ -356,6 +737,7 @@ mod tests {
         // Write initial data
         let write_options = CreateWritableOptions {
             keep_existing_data: false,
+            atomic: false,
         };
         let mut writer = file
             .create_writable_with_options(&write_options)
@@ -367,6 +749,7 @@ mod tests {
         // Write more data keeping existing
         let keep_options = CreateWritableOptions {
             keep_existing_data: true,
+            atomic: false,
         };
         let mut writer2 = file
             .create_writable_with_options(&keep_options)
@@ -395,6 +778,7 @@ mod tests {
         // Write initial data
         let write_options = CreateWritableOptions {
             keep_existing_data: false,
+            atomic: false,
         };
         let mut writer = file
             .create_writable_with_options(&write_options)
@@ -409,6 +793,7 @@ mod tests {
         // Truncate and write new data
         let truncate_options = CreateWritableOptions {
             keep_existing_data: false,
+            atomic: false,
         };
         let mut writer2 = file
             .create_writable_with_options(&truncate_options)
@@ -420,4 +805,370 @@ mod tests {
         let data = file.read().await.unwrap();
         assert_eq!(data, b"Hi");
     }
+
+    #[tokio::test]
+    async fn test_writable_stream_truncate_resizes_file() {
+        let (_temp_dir, dir) = setup_temp_dir().await;
+        let options = GetFileHandleOptions { create: true };
+
+        let mut file = dir
+            .get_file_handle_with_options("test.txt", &options)
+            .await
+            .unwrap();
+        let mut writer = file.create_writable().await.unwrap();
+        writer.write_at_cursor_pos(b"Hello, world!".to_vec()).await.unwrap();
+
+        writer.truncate(5).await.unwrap();
+        writer.close().await.unwrap();
+        assert_eq!(file.read().await.unwrap(), b"Hello");
+    }
+
+    #[tokio::test]
+    async fn test_atomic_write_swaps_into_place() {
+        let (_temp_dir, dir) = setup_temp_dir().await;
+        let options = GetFileHandleOptions { create: true };
+
+        let mut file = dir
+            .get_file_handle_with_options("test.txt", &options)
+            .await
+            .unwrap();
+
+        let write_options = CreateWritableOptions {
+            keep_existing_data: false,
+            atomic: true,
+        };
+        let mut writer = file
+            .create_writable_with_options(&write_options)
+            .await
+            .unwrap();
+        writer.write_at_cursor_pos(b"Hello, world!".to_vec()).await.unwrap();
+
+        // Until close() is called, the destination file must be untouched.
+        assert_eq!(file.read().await.unwrap(), b"");
+
+        writer.close().await.unwrap();
+
+        assert_eq!(file.read().await.unwrap(), b"Hello, world!");
+
+        // After close(), no stray temp file should be left in the directory listing.
+        let entries_stream = dir.entries().await.unwrap();
+        let names: Vec<_> = entries_stream
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .map(|r| r.unwrap().0)
+            .collect();
+        assert_eq!(names, vec!["test.txt"]);
+    }
+
+    #[tokio::test]
+    async fn test_atomic_write_keep_existing_data() {
+        let (_temp_dir, dir) = setup_temp_dir().await;
+        let options = GetFileHandleOptions { create: true };
+
+        let mut file = dir
+            .get_file_handle_with_options("test.txt", &options)
+            .await
+            .unwrap();
+
+        let mut writer = file
+            .create_writable_with_options(&CreateWritableOptions {
+                keep_existing_data: false,
+                atomic: false,
+            })
+            .await
+            .unwrap();
+        writer.write_at_cursor_pos(b"Hello".to_vec()).await.unwrap();
+        writer.close().await.unwrap();
+
+        let mut writer2 = file
+            .create_writable_with_options(&CreateWritableOptions {
+                keep_existing_data: true,
+                atomic: true,
+            })
+            .await
+            .unwrap();
+        writer2.seek(5).await.unwrap();
+        writer2.write_at_cursor_pos(b", world!".to_vec()).await.unwrap();
+        writer2.close().await.unwrap();
+
+        assert_eq!(file.read().await.unwrap(), b"Hello, world!");
+    }
+
+    #[tokio::test]
+    async fn test_atomic_abort_leaves_destination_untouched() {
+        let (_temp_dir, dir) = setup_temp_dir().await;
+        let options = GetFileHandleOptions { create: true };
+
+        let mut file = dir
+            .get_file_handle_with_options("test.txt", &options)
+            .await
+            .unwrap();
+
+        let mut writer = file
+            .create_writable_with_options(&CreateWritableOptions {
+                keep_existing_data: false,
+                atomic: true,
+            })
+            .await
+            .unwrap();
+        writer.write_at_cursor_pos(b"never committed".to_vec()).await.unwrap();
+        writer.abort().await.unwrap();
+
+        assert_eq!(file.read().await.unwrap(), b"");
+
+        let entries_stream = dir.entries().await.unwrap();
+        let names: Vec<_> = entries_stream
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .map(|r| r.unwrap().0)
+            .collect();
+        assert_eq!(names, vec!["test.txt"]);
+    }
+
+    #[tokio::test]
+    async fn test_rename_entry_moves_file_between_directories() {
+        let (_temp_dir, mut dir) = setup_temp_dir().await;
+        let mut file = dir
+            .get_file_handle_with_options("a.txt", &GetFileHandleOptions { create: true })
+            .await
+            .unwrap();
+        let mut writer = file.create_writable().await.unwrap();
+        writer.write_at_cursor_pos(b"hello".to_vec()).await.unwrap();
+        writer.close().await.unwrap();
+
+        let sub = dir
+            .get_directory_handle_with_options(
+                "sub",
+                &crate::GetDirectoryHandleOptions { create: true },
+            )
+            .await
+            .unwrap();
+
+        dir.rename_entry("a.txt", &sub, "b.txt").await.unwrap();
+
+        assert!(
+            dir.get_file_handle_with_options("a.txt", &GetFileHandleOptions { create: false })
+                .await
+                .is_err()
+        );
+        let moved = sub
+            .get_file_handle_with_options("b.txt", &GetFileHandleOptions { create: false })
+            .await
+            .unwrap();
+        assert_eq!(moved.read().await.unwrap(), b"hello");
+    }
+
+    #[tokio::test]
+    async fn test_copy_entry_duplicates_file_contents() {
+        let (_temp_dir, mut dir) = setup_temp_dir().await;
+        let mut file = dir
+            .get_file_handle_with_options("a.txt", &GetFileHandleOptions { create: true })
+            .await
+            .unwrap();
+        let mut writer = file.create_writable().await.unwrap();
+        writer.write_at_cursor_pos(b"hello".to_vec()).await.unwrap();
+        writer.close().await.unwrap();
+
+        dir.copy_entry("a.txt", &dir.clone(), "b.txt", &crate::CopyOptions::default())
+            .await
+            .unwrap();
+
+        assert_eq!(file.read().await.unwrap(), b"hello");
+        let copy = dir
+            .get_file_handle_with_options("b.txt", &GetFileHandleOptions { create: false })
+            .await
+            .unwrap();
+        assert_eq!(copy.read().await.unwrap(), b"hello");
+    }
+
+    #[tokio::test]
+    async fn test_copy_entry_directory_requires_recursive() {
+        let (_temp_dir, mut dir) = setup_temp_dir().await;
+        dir.get_directory_handle_with_options(
+            "sub",
+            &crate::GetDirectoryHandleOptions { create: true },
+        )
+        .await
+        .unwrap();
+
+        let result = dir
+            .copy_entry("sub", &dir.clone(), "sub2", &crate::CopyOptions::default())
+            .await;
+        assert!(result.is_err());
+
+        dir.copy_entry(
+            "sub",
+            &dir.clone(),
+            "sub2",
+            &crate::CopyOptions { overwrite: false, recursive: true },
+        )
+        .await
+        .unwrap();
+
+        dir.get_directory_handle_with_options("sub2", &crate::GetDirectoryHandleOptions { create: false })
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_metadata_reports_size_and_type() {
+        let (_temp_dir, dir) = setup_temp_dir().await;
+        let mut file = dir
+            .get_file_handle_with_options("test.txt", &GetFileHandleOptions { create: true })
+            .await
+            .unwrap();
+
+        let mut writer = file.create_writable().await.unwrap();
+        writer.write_at_cursor_pos(b"Hello".to_vec()).await.unwrap();
+        writer.close().await.unwrap();
+
+        let metadata = file.metadata().await.unwrap();
+        assert_eq!(metadata.len, 5);
+        assert_eq!(metadata.file_type, crate::FileType::File);
+        assert!(!metadata.readonly);
+
+        let dir_metadata = dir.metadata().await.unwrap();
+        assert_eq!(dir_metadata.file_type, crate::FileType::Directory);
+    }
+
+    #[tokio::test]
+    async fn test_set_readonly_marks_file_readonly() {
+        let (_temp_dir, dir) = setup_temp_dir().await;
+        let mut file = dir
+            .get_file_handle_with_options("test.txt", &GetFileHandleOptions { create: true })
+            .await
+            .unwrap();
+
+        file.set_readonly(true).await.unwrap();
+        assert!(file.metadata().await.unwrap().readonly);
+
+        file.set_readonly(false).await.unwrap();
+        assert!(!file.metadata().await.unwrap().readonly);
+    }
+
+    #[tokio::test]
+    async fn test_entry_metadata_stats_files_and_directories_without_a_handle() {
+        let (_temp_dir, dir) = setup_temp_dir().await;
+        let mut file = dir
+            .get_file_handle_with_options("test.txt", &GetFileHandleOptions { create: true })
+            .await
+            .unwrap();
+        let mut writer = file.create_writable().await.unwrap();
+        writer.write_at_cursor_pos(b"Hello".to_vec()).await.unwrap();
+        writer.close().await.unwrap();
+
+        let file_meta = crate::DirectoryHandle::entry_metadata(&dir, "test.txt")
+            .await
+            .unwrap();
+        assert_eq!(file_meta.len, 5);
+        assert_eq!(file_meta.file_type, crate::FileType::File);
+
+        dir.get_directory_handle_with_options(
+            "sub",
+            &crate::GetDirectoryHandleOptions { create: true },
+        )
+        .await
+        .unwrap();
+        let dir_meta = crate::DirectoryHandle::entry_metadata(&dir, "sub")
+            .await
+            .unwrap();
+        assert_eq!(dir_meta.file_type, crate::FileType::Directory);
+
+        assert!(
+            crate::DirectoryHandle::entry_metadata(&dir, "missing.txt")
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_range_clamps_to_remaining_bytes() {
+        let (_temp_dir, dir) = setup_temp_dir().await;
+        let mut file = dir
+            .get_file_handle_with_options("test.txt", &GetFileHandleOptions { create: true })
+            .await
+            .unwrap();
+
+        let mut writer = file.create_writable().await.unwrap();
+        writer.write_at_cursor_pos(b"Hello, world!".to_vec()).await.unwrap();
+        writer.close().await.unwrap();
+
+        assert_eq!(file.read_range(7, 5).await.unwrap(), b"world");
+        assert_eq!(file.read_range(7, 100).await.unwrap(), b"world!");
+        assert_eq!(file.read_range(100, 5).await.unwrap(), b"");
+    }
+
+    #[tokio::test]
+    async fn test_read_stream_reassembles_into_full_contents() {
+        let (_temp_dir, dir) = setup_temp_dir().await;
+        let mut file = dir
+            .get_file_handle_with_options("test.txt", &GetFileHandleOptions { create: true })
+            .await
+            .unwrap();
+
+        let data = vec![7u8; READ_STREAM_CHUNK_SIZE * 2 + 1];
+        let mut writer = file.create_writable().await.unwrap();
+        writer.write_at_cursor_pos(data.clone()).await.unwrap();
+        writer.close().await.unwrap();
+
+        let stream = file.read_stream().await.unwrap();
+        futures::pin_mut!(stream);
+        let mut chunks = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            chunks.push(chunk.unwrap());
+        }
+
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks.iter().map(|c| c.len()).sum::<usize>(), data.len());
+        assert_eq!(file.read().await.unwrap(), data);
+    }
+
+    #[tokio::test]
+    async fn test_watch_reports_created_file() {
+        let (_temp_dir, dir) = setup_temp_dir().await;
+
+        let stream = dir.watch().await.unwrap();
+        futures::pin_mut!(stream);
+
+        let options = GetFileHandleOptions { create: true };
+        let _file = dir
+            .get_file_handle_with_options("test.txt", &options)
+            .await
+            .unwrap();
+
+        let event = tokio::time::timeout(std::time::Duration::from_secs(5), stream.next())
+            .await
+            .expect("timed out waiting for a change event")
+            .expect("stream ended without an event");
+
+        assert_eq!(event.path, std::path::PathBuf::from("test.txt"));
+        assert_eq!(event.kind, crate::ChangeKind::Created);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_walk_respects_follow_symlinks() {
+        use crate::walk::{walk, WalkOptions};
+        use std::path::PathBuf;
+
+        let (temp_dir, root) = setup_temp_dir().await;
+        let target_dir = temp_dir.path().join("target");
+        std::fs::create_dir(&target_dir).unwrap();
+        std::fs::write(target_dir.join("inside.txt"), b"hi").unwrap();
+        std::os::unix::fs::symlink(&target_dir, temp_dir.path().join("link")).unwrap();
+
+        let stream = walk(&root, &WalkOptions { max_depth: None, follow_symlinks: false })
+            .await
+            .unwrap();
+        let paths: Vec<_> = stream.collect::<Vec<_>>().await.into_iter().map(|r| r.unwrap().0).collect();
+        assert!(!paths.contains(&PathBuf::from("link/inside.txt")));
+
+        let stream = walk(&root, &WalkOptions { max_depth: None, follow_symlinks: true })
+            .await
+            .unwrap();
+        let paths: Vec<_> = stream.collect::<Vec<_>>().await.into_iter().map(|r| r.unwrap().0).collect();
+        assert!(paths.contains(&PathBuf::from("link/inside.txt")));
+    }
 }
@@ -0,0 +1,672 @@
+//! A copy-on-write overlay combining a writable upper layer with a read-only lower layer.
+//!
+//! [`OverlayDirectoryHandle`] looks up entries in `Upper` first, falling through to `Lower` when
+//! absent; `entries()` merges both layers, with `Upper` winning on name collisions. Writing to a
+//! file that only exists in `Lower` copies its contents up into `Upper` first, so `Lower` is
+//! never mutated. Removing an entry that's still present in `Lower` records a whiteout marker
+//! (a `.wh.`-prefixed file, following the same convention overlay filesystems use) in `Upper` so
+//! it stays hidden without touching `Lower`. This mirrors wasmer-vfs's `StaticFileSystem`: a
+//! read-only bundled or archive-backed tree an app can transparently edit.
+//!
+//! Because a copy-up needs somewhere in `Upper` to land, descending into a directory that only
+//! exists in `Lower` eagerly creates the matching (empty) directory in `Upper` as the overlay
+//! traverses toward it, even via read-only calls like [`DirectoryHandle::entries`].
+
+use crate::{DirectoryEntry, DirectoryHandle, FileHandle, WritableFileStream};
+use futures::Stream;
+
+/// Name prefix used for whiteout markers recorded in `Upper`. A real entry named this way would
+/// be mistaken for one; this is a known, documented limitation rather than something tracked
+/// separately, matching the corpus's preference for small hand-rolled pieces over heavier state.
+const WHITEOUT_PREFIX: &str = ".wh.";
+
+fn whiteout_name(name: &str) -> String {
+    format!("{WHITEOUT_PREFIX}{name}")
+}
+
+/// Errors from an [`OverlayDirectoryHandle`], tagged with which layer produced them.
+#[derive(Debug)]
+pub enum OverlayError<L, U> {
+    Lower(L),
+    Upper(U),
+    NotFound(String),
+    AlreadyExists(String),
+    /// `name` is a directory; retry with [`crate::CopyOptions::recursive`] set.
+    RequiresRecursive(String),
+}
+
+type OverlayDirectoryEntry<Lower, Upper> =
+    DirectoryEntry<OverlayDirectoryHandle<Lower, Upper>, OverlayFileHandle<Lower, Upper>>;
+
+/// A directory layering a writable `Upper` backend over a read-only `Lower` backend.
+#[derive(Clone, Debug)]
+pub struct OverlayDirectoryHandle<Lower: DirectoryHandle, Upper: DirectoryHandle> {
+    lower: Option<Lower>,
+    upper: Upper,
+}
+
+/// A file in the overlay backend, resolved against `Upper` first and falling back to `Lower`.
+#[derive(Clone, Debug)]
+pub struct OverlayFileHandle<Lower: DirectoryHandle, Upper: DirectoryHandle> {
+    name: String,
+    upper_dir: Upper,
+    lower_file: Option<Lower::FileHandleT>,
+}
+
+/// A writable stream in the overlay backend. Always backed by `Upper`, since `Lower` is never
+/// written to.
+#[derive(Clone, Debug)]
+pub struct OverlayWritableFileStream<Lower: DirectoryHandle, Upper: DirectoryHandle> {
+    inner: <Upper::FileHandleT as FileHandle>::WritableFileStreamT,
+    _lower: std::marker::PhantomData<Lower>,
+}
+
+impl<Lower: DirectoryHandle, Upper: DirectoryHandle> crate::private::Sealed
+    for OverlayDirectoryHandle<Lower, Upper>
+{
+}
+impl<Lower: DirectoryHandle, Upper: DirectoryHandle> crate::private::Sealed
+    for OverlayFileHandle<Lower, Upper>
+{
+}
+impl<Lower: DirectoryHandle, Upper: DirectoryHandle> crate::private::Sealed
+    for OverlayWritableFileStream<Lower, Upper>
+{
+}
+
+impl<Lower: DirectoryHandle, Upper: DirectoryHandle> OverlayDirectoryHandle<Lower, Upper> {
+    /// Layers the writable `upper` over the read-only `lower`.
+    pub fn new(lower: Lower, upper: Upper) -> Self {
+        Self {
+            lower: Some(lower),
+            upper,
+        }
+    }
+
+    fn wrap_dir(&self, lower: Option<Lower>, upper: Upper) -> Self {
+        Self { lower, upper }
+    }
+
+    fn wrap_file(&self, name: &str, lower_file: Option<Lower::FileHandleT>) -> OverlayFileHandle<Lower, Upper> {
+        OverlayFileHandle {
+            name: name.to_string(),
+            upper_dir: self.upper.clone(),
+            lower_file,
+        }
+    }
+
+    async fn is_whited_out(&self, name: &str) -> Result<bool, Upper::Error> {
+        Ok(self
+            .upper
+            .get_file_handle_with_options(&whiteout_name(name), &crate::GetFileHandleOptions { create: false })
+            .await
+            .is_ok())
+    }
+}
+
+impl<Lower: DirectoryHandle, Upper: DirectoryHandle> DirectoryHandle for OverlayDirectoryHandle<Lower, Upper> {
+    type Error = OverlayError<Lower::Error, Upper::Error>;
+    type FileHandleT = OverlayFileHandle<Lower, Upper>;
+
+    async fn get_file_handle_with_options(
+        &self,
+        name: &str,
+        options: &crate::GetFileHandleOptions,
+    ) -> Result<Self::FileHandleT, Self::Error> {
+        if self
+            .upper
+            .get_file_handle_with_options(name, &crate::GetFileHandleOptions { create: false })
+            .await
+            .is_ok()
+        {
+            return Ok(self.wrap_file(name, None));
+        }
+
+        if !self.is_whited_out(name).await.map_err(OverlayError::Upper)? {
+            if let Some(lower) = &self.lower {
+                if let Ok(lower_file) = lower
+                    .get_file_handle_with_options(name, &crate::GetFileHandleOptions { create: false })
+                    .await
+                {
+                    return Ok(self.wrap_file(name, Some(lower_file)));
+                }
+            }
+        }
+
+        if options.create {
+            self.upper
+                .get_file_handle_with_options(name, &crate::GetFileHandleOptions { create: true })
+                .await
+                .map_err(OverlayError::Upper)?;
+            return Ok(self.wrap_file(name, None));
+        }
+
+        Err(OverlayError::NotFound(name.to_string()))
+    }
+
+    async fn get_directory_handle_with_options(
+        &self,
+        name: &str,
+        options: &crate::GetDirectoryHandleOptions,
+    ) -> Result<Self, Self::Error> {
+        let upper_dir = self
+            .upper
+            .get_directory_handle_with_options(name, &crate::GetDirectoryHandleOptions { create: false })
+            .await
+            .ok();
+
+        let lower_dir = if self.is_whited_out(name).await.map_err(OverlayError::Upper)? {
+            None
+        } else if let Some(lower) = &self.lower {
+            lower
+                .get_directory_handle_with_options(name, &crate::GetDirectoryHandleOptions { create: false })
+                .await
+                .ok()
+        } else {
+            None
+        };
+
+        if upper_dir.is_none() && lower_dir.is_none() && !options.create {
+            return Err(OverlayError::NotFound(name.to_string()));
+        }
+
+        let upper_dir = match upper_dir {
+            Some(dir) => dir,
+            None => self
+                .upper
+                .get_directory_handle_with_options(name, &crate::GetDirectoryHandleOptions { create: true })
+                .await
+                .map_err(OverlayError::Upper)?,
+        };
+
+        Ok(self.wrap_dir(lower_dir, upper_dir))
+    }
+
+    async fn metadata(&self) -> Result<crate::Metadata, Self::Error> {
+        self.upper.metadata().await.map_err(OverlayError::Upper)
+    }
+
+    async fn remove_entry(&mut self, name: &str) -> Result<(), Self::Error> {
+        let upper_had_it = self
+            .upper
+            .get_file_handle_with_options(name, &crate::GetFileHandleOptions { create: false })
+            .await
+            .is_ok()
+            || self
+                .upper
+                .get_directory_handle_with_options(name, &crate::GetDirectoryHandleOptions { create: false })
+                .await
+                .is_ok();
+
+        if upper_had_it {
+            self.upper.remove_entry(name).await.map_err(OverlayError::Upper)?;
+        }
+
+        let lower_had_it = if let Some(lower) = &self.lower {
+            lower
+                .get_file_handle_with_options(name, &crate::GetFileHandleOptions { create: false })
+                .await
+                .is_ok()
+                || lower
+                    .get_directory_handle_with_options(name, &crate::GetDirectoryHandleOptions { create: false })
+                    .await
+                    .is_ok()
+        } else {
+            false
+        };
+
+        if lower_had_it {
+            let mut marker = self
+                .upper
+                .get_file_handle_with_options(&whiteout_name(name), &crate::GetFileHandleOptions { create: true })
+                .await
+                .map_err(OverlayError::Upper)?;
+            let mut writer = marker.create_writable().await.map_err(OverlayError::Upper)?;
+            writer.close().await.map_err(OverlayError::Upper)?;
+        } else if !upper_had_it {
+            return Err(OverlayError::NotFound(name.to_string()));
+        }
+
+        Ok(())
+    }
+
+    async fn entry_metadata(&self, name: &str) -> Result<crate::Metadata, Self::Error> {
+        match self
+            .get_file_handle_with_options(name, &crate::GetFileHandleOptions { create: false })
+            .await
+        {
+            Ok(file) => file.metadata().await,
+            Err(_) => self
+                .get_directory_handle_with_options(name, &crate::GetDirectoryHandleOptions { create: false })
+                .await?
+                .metadata()
+                .await,
+        }
+    }
+
+    async fn rename_entry(&mut self, from: &str, to: &Self, new_name: &str) -> Result<(), Self::Error> {
+        let options = crate::CopyOptions {
+            overwrite: true,
+            recursive: true,
+        };
+        self.copy_entry(from, to, new_name, &options).await?;
+        self.remove_entry(from).await
+    }
+
+    async fn copy_entry(
+        &mut self,
+        name: &str,
+        dest: &Self,
+        new_name: &str,
+        options: &crate::CopyOptions,
+    ) -> Result<(), Self::Error> {
+        if let Ok(file) = self
+            .get_file_handle_with_options(name, &crate::GetFileHandleOptions { create: false })
+            .await
+        {
+            if !options.overwrite
+                && dest
+                    .get_file_handle_with_options(new_name, &crate::GetFileHandleOptions { create: false })
+                    .await
+                    .is_ok()
+            {
+                return Err(OverlayError::AlreadyExists(new_name.to_string()));
+            }
+
+            let data = file.read().await?;
+            let mut dest_file = dest
+                .get_file_handle_with_options(new_name, &crate::GetFileHandleOptions { create: true })
+                .await?;
+            let mut writer = dest_file.create_writable().await?;
+            writer.write_at_cursor_pos(data).await?;
+            writer.close().await?;
+            return Ok(());
+        }
+
+        let src_dir = self
+            .get_directory_handle_with_options(name, &crate::GetDirectoryHandleOptions { create: false })
+            .await
+            .map_err(|_| OverlayError::NotFound(name.to_string()))?;
+
+        if !options.recursive {
+            return Err(OverlayError::RequiresRecursive(name.to_string()));
+        }
+
+        let dest_dir = dest
+            .get_directory_handle_with_options(new_name, &crate::GetDirectoryHandleOptions { create: true })
+            .await?;
+        copy_overlay_tree(src_dir, dest_dir, options.overwrite).await
+    }
+
+    async fn entries(
+        &self,
+    ) -> Result<impl Stream<Item = Result<(String, OverlayDirectoryEntry<Lower, Upper>), Self::Error>>, Self::Error>
+    {
+        use futures::{StreamExt, pin_mut};
+
+        let mut results = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+
+        let upper_entries = self.upper.entries().await.map_err(OverlayError::Upper)?;
+        pin_mut!(upper_entries);
+        while let Some(entry) = upper_entries.next().await {
+            let (name, entry) = entry.map_err(OverlayError::Upper)?;
+            if name.starts_with(WHITEOUT_PREFIX) {
+                continue;
+            }
+            seen.insert(name.clone());
+
+            let mapped = match entry {
+                DirectoryEntry::File(_) => DirectoryEntry::File(self.wrap_file(&name, None)),
+                DirectoryEntry::Directory(upper_dir) => {
+                    let lower_dir = if self.is_whited_out(&name).await.map_err(OverlayError::Upper)? {
+                        None
+                    } else if let Some(lower) = &self.lower {
+                        lower
+                            .get_directory_handle_with_options(&name, &crate::GetDirectoryHandleOptions { create: false })
+                            .await
+                            .ok()
+                    } else {
+                        None
+                    };
+                    DirectoryEntry::Directory(self.wrap_dir(lower_dir, upper_dir))
+                }
+            };
+            results.push(Ok((name, mapped)));
+        }
+
+        if let Some(lower) = &self.lower {
+            let lower_entries = lower.entries().await.map_err(OverlayError::Lower)?;
+            pin_mut!(lower_entries);
+            while let Some(entry) = lower_entries.next().await {
+                let (name, entry) = entry.map_err(OverlayError::Lower)?;
+                if seen.contains(&name) || self.is_whited_out(&name).await.map_err(OverlayError::Upper)? {
+                    continue;
+                }
+
+                let mapped = match entry {
+                    DirectoryEntry::File(lower_file) => {
+                        DirectoryEntry::File(self.wrap_file(&name, Some(lower_file)))
+                    }
+                    DirectoryEntry::Directory(lower_dir) => {
+                        let upper_dir = self
+                            .upper
+                            .get_directory_handle_with_options(&name, &crate::GetDirectoryHandleOptions { create: true })
+                            .await
+                            .map_err(OverlayError::Upper)?;
+                        DirectoryEntry::Directory(self.wrap_dir(Some(lower_dir), upper_dir))
+                    }
+                };
+                results.push(Ok((name, mapped)));
+            }
+        }
+
+        Ok(futures::stream::iter(results))
+    }
+
+    async fn watch_with_options(
+        &self,
+        options: &crate::WatchOptions,
+    ) -> Result<impl Stream<Item = crate::ChangeEvent> + 'static, Self::Error> {
+        self.upper.watch_with_options(options).await.map_err(OverlayError::Upper)
+    }
+}
+
+/// Recursively copies every entry under `src` into `dest`, using a work queue rather than
+/// `async fn` recursion, as with [`crate::walk::walk`].
+async fn copy_overlay_tree<Lower: DirectoryHandle, Upper: DirectoryHandle>(
+    src: OverlayDirectoryHandle<Lower, Upper>,
+    dest: OverlayDirectoryHandle<Lower, Upper>,
+    overwrite: bool,
+) -> Result<(), OverlayError<Lower::Error, Upper::Error>> {
+    use futures::{StreamExt, pin_mut};
+
+    let mut queue = std::collections::VecDeque::new();
+    queue.push_back((src, dest));
+
+    while let Some((src_dir, dest_dir)) = queue.pop_front() {
+        let entries = src_dir.entries().await?;
+        pin_mut!(entries);
+
+        while let Some(entry) = entries.next().await {
+            let (name, entry) = entry?;
+            match entry {
+                DirectoryEntry::File(file) => {
+                    if !overwrite
+                        && dest_dir
+                            .get_file_handle_with_options(&name, &crate::GetFileHandleOptions { create: false })
+                            .await
+                            .is_ok()
+                    {
+                        return Err(OverlayError::AlreadyExists(name));
+                    }
+
+                    let data = file.read().await?;
+                    let mut dest_file = dest_dir
+                        .get_file_handle_with_options(&name, &crate::GetFileHandleOptions { create: true })
+                        .await?;
+                    let mut writer = dest_file.create_writable().await?;
+                    writer.write_at_cursor_pos(data).await?;
+                    writer.close().await?;
+                }
+                DirectoryEntry::Directory(child_src) => {
+                    let child_dest = dest_dir
+                        .get_directory_handle_with_options(&name, &crate::GetDirectoryHandleOptions { create: true })
+                        .await?;
+                    queue.push_back((child_src, child_dest));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+impl<Lower: DirectoryHandle, Upper: DirectoryHandle> OverlayFileHandle<Lower, Upper> {
+    async fn resolve_upper(&self) -> Option<Upper::FileHandleT> {
+        self.upper_dir
+            .get_file_handle_with_options(&self.name, &crate::GetFileHandleOptions { create: false })
+            .await
+            .ok()
+    }
+
+    /// Returns the file's `Upper` handle, copying `Lower`'s current contents up into it first if
+    /// it doesn't exist there yet.
+    async fn ensure_upper(&self) -> Result<Upper::FileHandleT, OverlayError<Lower::Error, Upper::Error>> {
+        if let Some(file) = self.resolve_upper().await {
+            return Ok(file);
+        }
+
+        let mut upper_file = self
+            .upper_dir
+            .get_file_handle_with_options(&self.name, &crate::GetFileHandleOptions { create: true })
+            .await
+            .map_err(OverlayError::Upper)?;
+
+        if let Some(lower_file) = &self.lower_file {
+            let data = lower_file.read().await.map_err(OverlayError::Lower)?;
+            let mut seed = upper_file
+                .create_writable_with_options(&crate::CreateWritableOptions::default())
+                .await
+                .map_err(OverlayError::Upper)?;
+            seed.write_at_cursor_pos(data).await.map_err(OverlayError::Upper)?;
+            seed.close().await.map_err(OverlayError::Upper)?;
+        }
+
+        Ok(upper_file)
+    }
+}
+
+impl<Lower: DirectoryHandle, Upper: DirectoryHandle> FileHandle for OverlayFileHandle<Lower, Upper> {
+    type Error = OverlayError<Lower::Error, Upper::Error>;
+    type WritableFileStreamT = OverlayWritableFileStream<Lower, Upper>;
+
+    async fn create_writable_with_options(
+        &mut self,
+        options: &crate::CreateWritableOptions,
+    ) -> Result<Self::WritableFileStreamT, Self::Error> {
+        let mut upper_file = self.ensure_upper().await?;
+        let inner = upper_file
+            .create_writable_with_options(options)
+            .await
+            .map_err(OverlayError::Upper)?;
+        Ok(OverlayWritableFileStream {
+            inner,
+            _lower: std::marker::PhantomData,
+        })
+    }
+
+    async fn size(&self) -> Result<usize, Self::Error> {
+        if let Some(upper_file) = self.resolve_upper().await {
+            return upper_file.size().await.map_err(OverlayError::Upper);
+        }
+        let lower_file = self
+            .lower_file
+            .as_ref()
+            .ok_or_else(|| OverlayError::NotFound(self.name.clone()))?;
+        lower_file.size().await.map_err(OverlayError::Lower)
+    }
+
+    async fn read_range(&self, offset: usize, len: usize) -> Result<Vec<u8>, Self::Error> {
+        if let Some(upper_file) = self.resolve_upper().await {
+            return upper_file.read_range(offset, len).await.map_err(OverlayError::Upper);
+        }
+        let lower_file = self
+            .lower_file
+            .as_ref()
+            .ok_or_else(|| OverlayError::NotFound(self.name.clone()))?;
+        lower_file.read_range(offset, len).await.map_err(OverlayError::Lower)
+    }
+
+    async fn read_stream(
+        &self,
+    ) -> Result<impl Stream<Item = Result<bytes::Bytes, Self::Error>>, Self::Error> {
+        if let Some(upper_file) = self.resolve_upper().await {
+            let data = upper_file.read().await.map_err(OverlayError::Upper)?;
+            return Ok(futures::stream::iter(vec![Ok(bytes::Bytes::from(data))]));
+        }
+        let lower_file = self
+            .lower_file
+            .as_ref()
+            .ok_or_else(|| OverlayError::NotFound(self.name.clone()))?;
+        let data = lower_file.read().await.map_err(OverlayError::Lower)?;
+        Ok(futures::stream::iter(vec![Ok(bytes::Bytes::from(data))]))
+    }
+
+    async fn metadata(&self) -> Result<crate::Metadata, Self::Error> {
+        if let Some(upper_file) = self.resolve_upper().await {
+            return upper_file.metadata().await.map_err(OverlayError::Upper);
+        }
+        let lower_file = self
+            .lower_file
+            .as_ref()
+            .ok_or_else(|| OverlayError::NotFound(self.name.clone()))?;
+        lower_file.metadata().await.map_err(OverlayError::Lower)
+    }
+
+    async fn set_readonly(&mut self, readonly: bool) -> Result<(), Self::Error> {
+        let mut upper_file = self.ensure_upper().await?;
+        upper_file.set_readonly(readonly).await.map_err(OverlayError::Upper)
+    }
+}
+
+impl<Lower: DirectoryHandle, Upper: DirectoryHandle> WritableFileStream for OverlayWritableFileStream<Lower, Upper> {
+    type Error = OverlayError<Lower::Error, Upper::Error>;
+
+    async fn write_at_cursor_pos(&mut self, data: Vec<u8>) -> Result<(), Self::Error> {
+        self.inner.write_at_cursor_pos(data).await.map_err(OverlayError::Upper)
+    }
+
+    async fn close(&mut self) -> Result<(), Self::Error> {
+        self.inner.close().await.map_err(OverlayError::Upper)
+    }
+
+    async fn seek(&mut self, offset: usize) -> Result<(), Self::Error> {
+        self.inner.seek(offset).await.map_err(OverlayError::Upper)
+    }
+
+    async fn truncate(&mut self, size: usize) -> Result<(), Self::Error> {
+        self.inner.truncate(size).await.map_err(OverlayError::Upper)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::DirectoryHandle as MemoryDirectoryHandle;
+    use crate::{DirectoryHandle as _, FileHandle as _, GetFileHandleOptions, WritableFileStream as _};
+
+    async fn write_file(dir: &MemoryDirectoryHandle, name: &str, contents: &[u8]) {
+        let mut file = dir
+            .get_file_handle_with_options(name, &GetFileHandleOptions { create: true })
+            .await
+            .unwrap();
+        let mut writer = file.create_writable().await.unwrap();
+        writer.write_at_cursor_pos(contents.to_vec()).await.unwrap();
+        writer.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_lookup_falls_through_to_lower_when_missing_in_upper() {
+        let lower = MemoryDirectoryHandle::default();
+        write_file(&lower, "a.txt", b"from lower").await;
+        let upper = MemoryDirectoryHandle::default();
+
+        let overlay = OverlayDirectoryHandle::new(lower, upper);
+        let file = overlay.get_file_handle("a.txt").await.unwrap();
+        assert_eq!(file.read().await.unwrap(), b"from lower");
+    }
+
+    #[tokio::test]
+    async fn test_write_triggers_copy_up_without_mutating_lower() {
+        let lower = MemoryDirectoryHandle::default();
+        write_file(&lower, "a.txt", b"original").await;
+        let upper = MemoryDirectoryHandle::default();
+
+        let mut overlay = OverlayDirectoryHandle::new(lower.clone(), upper);
+        let mut file = overlay.get_file_handle("a.txt").await.unwrap();
+        let mut writer = file.create_writable().await.unwrap();
+        writer.write_at_cursor_pos(b"edited".to_vec()).await.unwrap();
+        writer.close().await.unwrap();
+
+        assert_eq!(file.read().await.unwrap(), b"edited");
+
+        let lower_file = lower.get_file_handle("a.txt").await.unwrap();
+        assert_eq!(lower_file.read().await.unwrap(), b"original");
+    }
+
+    #[tokio::test]
+    async fn test_entries_merges_layers_with_upper_winning() {
+        let lower = MemoryDirectoryHandle::default();
+        write_file(&lower, "shared.txt", b"lower version").await;
+        write_file(&lower, "lower_only.txt", b"only in lower").await;
+        let upper = MemoryDirectoryHandle::default();
+        write_file(&upper, "shared.txt", b"upper version").await;
+
+        let overlay = OverlayDirectoryHandle::new(lower, upper);
+        let stream = overlay.entries().await.unwrap();
+        futures::pin_mut!(stream);
+
+        let mut names = Vec::new();
+        let mut shared_contents = None;
+        while let Some(entry) = futures::StreamExt::next(&mut stream).await {
+            let (name, entry) = entry.unwrap();
+            if let DirectoryEntry::File(file) = entry {
+                if name == "shared.txt" {
+                    shared_contents = Some(file.read().await.unwrap());
+                }
+            }
+            names.push(name);
+        }
+        names.sort();
+
+        assert_eq!(names, vec!["lower_only.txt", "shared.txt"]);
+        assert_eq!(shared_contents.unwrap(), b"upper version");
+    }
+
+    #[tokio::test]
+    async fn test_file_level_ops_resolve_through_both_layers() {
+        // Regression test: OverlayFileHandle/OverlayWritableFileStream map_err into
+        // OverlayError::Lower/Upper on every file- and stream-level call (size, metadata,
+        // read_range, write_at_cursor_pos, ...), which only type-checks because
+        // DirectoryHandle::FileHandleT and FileHandle::WritableFileStreamT tie their Error
+        // back to the outer trait's Error.
+        let lower = MemoryDirectoryHandle::default();
+        write_file(&lower, "a.txt", b"from lower").await;
+        let upper = MemoryDirectoryHandle::default();
+
+        let mut overlay = OverlayDirectoryHandle::new(lower, upper);
+
+        let lower_file = overlay.get_file_handle("a.txt").await.unwrap();
+        assert_eq!(lower_file.size().await.unwrap(), "from lower".len());
+        assert_eq!(lower_file.read_range(0, 4).await.unwrap(), b"from");
+        lower_file.metadata().await.unwrap();
+
+        let mut file = overlay.get_file_handle("a.txt").await.unwrap();
+        let mut writer = file.create_writable().await.unwrap();
+        writer.write_at_cursor_pos(b"from upper".to_vec()).await.unwrap();
+        writer.close().await.unwrap();
+
+        let upper_file = overlay.get_file_handle("a.txt").await.unwrap();
+        assert_eq!(upper_file.size().await.unwrap(), "from upper".len());
+        upper_file.metadata().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_remove_entry_records_whiteout_and_hides_lower_entry() {
+        let lower = MemoryDirectoryHandle::default();
+        write_file(&lower, "a.txt", b"hello").await;
+        let upper = MemoryDirectoryHandle::default();
+
+        let mut overlay = OverlayDirectoryHandle::new(lower.clone(), upper);
+        overlay.remove_entry("a.txt").await.unwrap();
+
+        assert!(matches!(
+            overlay.get_file_handle("a.txt").await,
+            Err(OverlayError::NotFound(_))
+        ));
+
+        // The lower layer is never actually mutated.
+        assert!(lower.get_file_handle("a.txt").await.is_ok());
+    }
+}
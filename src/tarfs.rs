@@ -0,0 +1,462 @@
+//! A read-only backend that mounts a tar archive's bytes as a navigable directory tree.
+//!
+//! [`TarfsDirectoryHandle::from_tar`] parses the whole archive once, up front, into a tree of
+//! directory/file nodes recording each file's byte range within the archive, so [`entries`]
+//! and the recursive [`crate::walk::walk`] work without re-scanning the archive bytes on every
+//! lookup. The mount is immutable: [`create_writable_with_options`] and [`remove_entry`] always
+//! fail. Pair this with [`crate::overlay`] to let callers write changes into a separate writable
+//! layer while the bundled archive itself never changes — this is the same role a webc fileblock
+//! plays for wasmer-vfs and a pxar archive plays for proxmox-backup.
+//!
+//! Only the USTAR format [`crate::archive`] already reads/writes is supported; zip archives
+//! aren't handled (see that module's doc comment for why this crate hand-rolls tar parsing
+//! instead of pulling in a dependency for it).
+//!
+//! [`entries`]: crate::DirectoryHandle::entries
+//! [`create_writable_with_options`]: crate::FileHandle::create_writable_with_options
+//! [`remove_entry`]: crate::DirectoryHandle::remove_entry
+
+use crate::{DirectoryEntry, FileHandle, WritableFileStream};
+use bytes::Bytes;
+use futures::Stream;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Chunk size used by [`FileHandle::read_stream`](crate::FileHandle::read_stream).
+const READ_STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+const BLOCK_SIZE: usize = 512;
+const TYPE_DIRECTORY: u8 = b'5';
+
+/// Failure modes for the tar-mount backend.
+#[derive(Debug)]
+pub enum TarfsError {
+    NotFound(String),
+    NotAFile(String),
+    NotADirectory(String),
+    /// The mount is immutable; `name` was the entry a caller tried to write to, remove, or
+    /// create.
+    ReadOnly(String),
+    /// The archive bytes themselves were truncated or malformed.
+    Malformed(String),
+}
+
+/// An entry in a mounted tar archive's virtual directory tree.
+pub type TarfsDirectoryEntry = DirectoryEntry<TarfsDirectoryHandle, TarfsFileHandle>;
+
+#[derive(Debug)]
+enum TarNode {
+    File { offset: usize, len: usize },
+    Directory(HashMap<String, TarNode>),
+}
+
+impl TarNode {
+    fn as_directory(&self) -> Option<&HashMap<String, TarNode>> {
+        match self {
+            TarNode::Directory(children) => Some(children),
+            TarNode::File { .. } => None,
+        }
+    }
+
+    fn as_directory_mut(&mut self, name: &str) -> Result<&mut HashMap<String, TarNode>, TarfsError> {
+        match self {
+            TarNode::Directory(children) => Ok(children),
+            TarNode::File { .. } => Err(TarfsError::Malformed(format!(
+                "'{name}' is both a file and a directory in the archive"
+            ))),
+        }
+    }
+}
+
+fn read_octal(field: &[u8]) -> u64 {
+    let text = std::str::from_utf8(field).unwrap_or("");
+    let trimmed = text.trim_matches(|c: char| c == '\0' || c == ' ');
+    u64::from_str_radix(trimmed, 8).unwrap_or(0)
+}
+
+fn read_name(field: &[u8]) -> String {
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).to_string()
+}
+
+const fn padding_len(size: usize) -> usize {
+    (BLOCK_SIZE - (size % BLOCK_SIZE)) % BLOCK_SIZE
+}
+
+/// Parses `data` as a USTAR byte stream into a directory tree rooted at `TarNode::Directory`.
+fn parse(data: &[u8]) -> Result<TarNode, TarfsError> {
+    let mut root = HashMap::new();
+    let mut offset = 0usize;
+
+    while offset + BLOCK_SIZE <= data.len() {
+        let header = &data[offset..offset + BLOCK_SIZE];
+        if header.iter().all(|&b| b == 0) {
+            break;
+        }
+
+        let name = read_name(&header[0..100]);
+        let size = read_octal(&header[124..136]) as usize;
+        let kind = header[156];
+        offset += BLOCK_SIZE;
+
+        let content_offset = offset;
+        if content_offset + size > data.len() {
+            return Err(TarfsError::Malformed(format!("'{name}' overruns the archive")));
+        }
+
+        let components: Vec<&str> = name.trim_end_matches('/').split('/').filter(|p| !p.is_empty()).collect();
+
+        if !components.is_empty() {
+            if kind == TYPE_DIRECTORY {
+                let mut dir = &mut root;
+                for part in &components {
+                    dir = dir
+                        .entry(part.to_string())
+                        .or_insert_with(|| TarNode::Directory(HashMap::new()))
+                        .as_directory_mut(part)?;
+                }
+            } else {
+                let (dir_parts, file_name) = components.split_at(components.len() - 1);
+                let mut dir = &mut root;
+                for part in dir_parts {
+                    dir = dir
+                        .entry(part.to_string())
+                        .or_insert_with(|| TarNode::Directory(HashMap::new()))
+                        .as_directory_mut(part)?;
+                }
+                dir.insert(
+                    file_name[0].to_string(),
+                    TarNode::File { offset: content_offset, len: size },
+                );
+            }
+        }
+
+        offset = content_offset + size + padding_len(size);
+    }
+
+    Ok(TarNode::Directory(root))
+}
+
+/// A directory in a mounted tar archive, read-only.
+#[derive(Clone, Debug)]
+pub struct TarfsDirectoryHandle {
+    data: Rc<Vec<u8>>,
+    root: Rc<TarNode>,
+    /// This directory's path from the archive root, walked through `root` on every lookup
+    /// rather than holding a direct reference, so the handle stays a cheap, independently
+    /// cloneable value (mirroring [`crate::cache::CachingDirectoryHandle`]'s `key_prefix`).
+    path: Vec<String>,
+}
+
+/// A file in a mounted tar archive, read-only.
+#[derive(Clone, Debug)]
+pub struct TarfsFileHandle {
+    data: Rc<Vec<u8>>,
+    name: String,
+    offset: usize,
+    len: usize,
+}
+
+/// The [`WritableFileStream`] associated type for [`TarfsFileHandle`]. Never actually
+/// constructed, since [`TarfsFileHandle::create_writable_with_options`] always fails; the
+/// wrapped [`std::convert::Infallible`] makes that statically visible.
+#[derive(Clone, Debug)]
+pub struct TarfsWritableFileStream(std::convert::Infallible);
+
+impl crate::private::Sealed for TarfsDirectoryHandle {}
+impl crate::private::Sealed for TarfsFileHandle {}
+impl crate::private::Sealed for TarfsWritableFileStream {}
+
+impl TarfsDirectoryHandle {
+    /// Parses `data` as a tar archive and mounts its root as a directory.
+    pub fn from_tar(data: Vec<u8>) -> Result<Self, TarfsError> {
+        let root = parse(&data)?;
+        Ok(Self {
+            data: Rc::new(data),
+            root: Rc::new(root),
+            path: Vec::new(),
+        })
+    }
+
+    fn current_dir(&self) -> &HashMap<String, TarNode> {
+        let mut node = self.root.as_directory().expect("archive root is always a directory");
+        for part in &self.path {
+            node = node
+                .get(part)
+                .and_then(TarNode::as_directory)
+                .expect("path was only ever extended through successful directory lookups");
+        }
+        node
+    }
+
+    fn wrap_dir(&self, name: &str) -> Self {
+        let mut path = self.path.clone();
+        path.push(name.to_string());
+        Self {
+            data: self.data.clone(),
+            root: self.root.clone(),
+            path,
+        }
+    }
+
+    fn wrap_file(&self, name: &str, offset: usize, len: usize) -> TarfsFileHandle {
+        TarfsFileHandle {
+            data: self.data.clone(),
+            name: name.to_string(),
+            offset,
+            len,
+        }
+    }
+}
+
+impl crate::DirectoryHandle for TarfsDirectoryHandle {
+    type Error = TarfsError;
+    type FileHandleT = TarfsFileHandle;
+
+    async fn get_file_handle_with_options(
+        &self,
+        name: &str,
+        options: &crate::GetFileHandleOptions,
+    ) -> Result<Self::FileHandleT, Self::Error> {
+        match self.current_dir().get(name) {
+            Some(TarNode::File { offset, len }) => Ok(self.wrap_file(name, *offset, *len)),
+            Some(TarNode::Directory(_)) => Err(TarfsError::NotAFile(name.to_string())),
+            None if options.create => Err(TarfsError::ReadOnly(name.to_string())),
+            None => Err(TarfsError::NotFound(name.to_string())),
+        }
+    }
+
+    async fn get_directory_handle_with_options(
+        &self,
+        name: &str,
+        options: &crate::GetDirectoryHandleOptions,
+    ) -> Result<Self, Self::Error> {
+        match self.current_dir().get(name) {
+            Some(TarNode::Directory(_)) => Ok(self.wrap_dir(name)),
+            Some(TarNode::File { .. }) => Err(TarfsError::NotADirectory(name.to_string())),
+            None if options.create => Err(TarfsError::ReadOnly(name.to_string())),
+            None => Err(TarfsError::NotFound(name.to_string())),
+        }
+    }
+
+    async fn metadata(&self) -> Result<crate::Metadata, Self::Error> {
+        Ok(crate::Metadata {
+            len: 0,
+            file_type: crate::FileType::Directory,
+            created: None,
+            modified: None,
+            accessed: None,
+            readonly: true,
+        })
+    }
+
+    async fn remove_entry(&mut self, name: &str) -> Result<(), Self::Error> {
+        Err(TarfsError::ReadOnly(name.to_string()))
+    }
+
+    async fn rename_entry(&mut self, from: &str, _to: &Self, _new_name: &str) -> Result<(), Self::Error> {
+        Err(TarfsError::ReadOnly(from.to_string()))
+    }
+
+    async fn copy_entry(
+        &mut self,
+        _name: &str,
+        _dest: &Self,
+        new_name: &str,
+        _options: &crate::CopyOptions,
+    ) -> Result<(), Self::Error> {
+        Err(TarfsError::ReadOnly(new_name.to_string()))
+    }
+
+    async fn entries(
+        &self,
+    ) -> Result<impl Stream<Item = Result<(String, TarfsDirectoryEntry), Self::Error>>, Self::Error> {
+        let entries: Vec<_> = self
+            .current_dir()
+            .iter()
+            .map(|(name, node)| {
+                let entry = match node {
+                    TarNode::File { offset, len } => DirectoryEntry::File(self.wrap_file(name, *offset, *len)),
+                    TarNode::Directory(_) => DirectoryEntry::Directory(self.wrap_dir(name)),
+                };
+                Ok((name.clone(), entry))
+            })
+            .collect();
+        Ok(futures::stream::iter(entries))
+    }
+
+    async fn watch_with_options(
+        &self,
+        _options: &crate::WatchOptions,
+    ) -> Result<impl Stream<Item = crate::ChangeEvent> + 'static, Self::Error> {
+        // The mount never changes, so there's nothing to ever report, but the watch itself is
+        // still a valid (permanently idle) subscription rather than an error.
+        Ok(futures::stream::pending())
+    }
+}
+
+impl FileHandle for TarfsFileHandle {
+    type Error = TarfsError;
+    type WritableFileStreamT = TarfsWritableFileStream;
+
+    async fn create_writable_with_options(
+        &mut self,
+        _options: &crate::CreateWritableOptions,
+    ) -> Result<Self::WritableFileStreamT, Self::Error> {
+        Err(TarfsError::ReadOnly(self.name.clone()))
+    }
+
+    async fn size(&self) -> Result<usize, Self::Error> {
+        Ok(self.len)
+    }
+
+    async fn read_range(&self, offset: usize, len: usize) -> Result<Vec<u8>, Self::Error> {
+        if offset >= self.len {
+            return Ok(Vec::new());
+        }
+        let end = (offset + len).min(self.len);
+        Ok(self.data[self.offset + offset..self.offset + end].to_vec())
+    }
+
+    async fn read_stream(&self) -> Result<impl Stream<Item = Result<Bytes, Self::Error>>, Self::Error> {
+        let bytes = &self.data[self.offset..self.offset + self.len];
+        let chunks: Vec<_> = bytes
+            .chunks(READ_STREAM_CHUNK_SIZE)
+            .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+            .collect();
+        Ok(futures::stream::iter(chunks))
+    }
+
+    async fn metadata(&self) -> Result<crate::Metadata, Self::Error> {
+        Ok(crate::Metadata {
+            len: self.len as u64,
+            file_type: crate::FileType::File,
+            created: None,
+            modified: None,
+            accessed: None,
+            readonly: true,
+        })
+    }
+
+    async fn set_readonly(&mut self, readonly: bool) -> Result<(), Self::Error> {
+        if readonly {
+            Ok(())
+        } else {
+            Err(TarfsError::ReadOnly(self.name.clone()))
+        }
+    }
+}
+
+impl WritableFileStream for TarfsWritableFileStream {
+    type Error = TarfsError;
+
+    async fn write_at_cursor_pos(&mut self, _data: Vec<u8>) -> Result<(), Self::Error> {
+        match self.0 {}
+    }
+
+    async fn close(&mut self) -> Result<(), Self::Error> {
+        match self.0 {}
+    }
+
+    async fn seek(&mut self, _offset: usize) -> Result<(), Self::Error> {
+        match self.0 {}
+    }
+
+    async fn truncate(&mut self, _size: usize) -> Result<(), Self::Error> {
+        match self.0 {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectoryHandle as _, FileHandle as _};
+
+    fn write_octal(field: &mut [u8], mut value: u64) {
+        let width = field.len() - 1;
+        for i in (0..width).rev() {
+            field[i] = b'0' + (value % 8) as u8;
+            value /= 8;
+        }
+        field[width] = 0;
+    }
+
+    fn header_block(name: &str, kind: u8, size: u64) -> [u8; BLOCK_SIZE] {
+        let mut block = [0u8; BLOCK_SIZE];
+        block[0..name.len()].copy_from_slice(name.as_bytes());
+        write_octal(&mut block[100..108], if kind == TYPE_DIRECTORY { 0o755 } else { 0o644 });
+        write_octal(&mut block[124..136], size);
+        block[156] = kind;
+        block[257..263].copy_from_slice(b"ustar\0");
+        block
+    }
+
+    fn build_archive() -> Vec<u8> {
+        let mut archive = Vec::new();
+        archive.extend_from_slice(&header_block("a.txt", b'0', 13));
+        archive.extend_from_slice(b"hello, world!");
+        archive.extend_from_slice(&[0u8; padding_len(13)]);
+        archive.extend_from_slice(&header_block("sub/", TYPE_DIRECTORY, 0));
+        archive.extend_from_slice(&header_block("sub/b.txt", b'0', 5));
+        archive.extend_from_slice(b"hello");
+        archive.extend_from_slice(&[0u8; padding_len(5)]);
+        archive.extend_from_slice(&[0u8; BLOCK_SIZE * 2]);
+        archive
+    }
+
+    #[tokio::test]
+    async fn test_mounts_archive_as_navigable_tree() {
+        let root = TarfsDirectoryHandle::from_tar(build_archive()).unwrap();
+
+        let a = root.get_file_handle("a.txt").await.unwrap();
+        assert_eq!(a.read().await.unwrap(), b"hello, world!");
+
+        let sub = root.get_directory_handle("sub").await.unwrap();
+        let b = sub.get_file_handle("b.txt").await.unwrap();
+        assert_eq!(b.read().await.unwrap(), b"hello");
+    }
+
+    #[tokio::test]
+    async fn test_entries_lists_files_and_directories() {
+        let root = TarfsDirectoryHandle::from_tar(build_archive()).unwrap();
+        let stream = root.entries().await.unwrap();
+        let mut names: Vec<_> =
+            futures::StreamExt::collect::<Vec<_>>(stream).await.into_iter().map(|r| r.unwrap().0).collect();
+        names.sort();
+        assert_eq!(names, vec!["a.txt", "sub"]);
+    }
+
+    #[tokio::test]
+    async fn test_walk_descends_into_mounted_directories() {
+        let root = TarfsDirectoryHandle::from_tar(build_archive()).unwrap();
+        let stream = root.walk().await.unwrap();
+        let mut paths: Vec<_> = futures::StreamExt::collect::<Vec<_>>(stream)
+            .await
+            .into_iter()
+            .map(|r| r.unwrap().0)
+            .collect();
+        paths.sort();
+
+        assert_eq!(
+            paths,
+            vec![
+                std::path::PathBuf::from("a.txt"),
+                std::path::PathBuf::from("sub"),
+                std::path::PathBuf::from("sub/b.txt"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mount_is_immutable() {
+        let mut root = TarfsDirectoryHandle::from_tar(build_archive()).unwrap();
+
+        let mut file = root.get_file_handle("a.txt").await.unwrap();
+        assert!(matches!(
+            file.create_writable().await,
+            Err(TarfsError::ReadOnly(_))
+        ));
+
+        assert!(matches!(root.remove_entry("a.txt").await, Err(TarfsError::ReadOnly(_))));
+    }
+}
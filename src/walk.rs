@@ -0,0 +1,301 @@
+//! Recursive directory traversal and search, built generically on top of
+//! [`DirectoryHandle::entries`](crate::DirectoryHandle::entries) so it works uniformly across
+//! backends.
+
+use crate::{DirectoryEntry, DirectoryHandle, FileHandle};
+use futures::{pin_mut, Stream, StreamExt};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+
+/// Options accepted by [`DirectoryHandle::walk_with_options`](crate::DirectoryHandle::walk_with_options).
+pub struct WalkOptions {
+    /// Maximum number of directory levels to descend. `None` means unbounded.
+    pub max_depth: Option<usize>,
+    /// Whether to follow symlinked directories while descending (backends that can't tell a
+    /// symlink from a real directory simply ignore this).
+    pub follow_symlinks: bool,
+}
+
+impl Default for WalkOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: None,
+            follow_symlinks: false,
+        }
+    }
+}
+
+/// A pattern matched against a file's name while [`search`]ing a tree.
+pub enum NamePattern {
+    /// A shell-style glob supporting `*` (any run of characters) and `?` (a single character).
+    Glob(String),
+    /// A regular expression matched against the full filename.
+    Regex(regex::Regex),
+}
+
+impl NamePattern {
+    fn matches(&self, name: &str) -> bool {
+        match self {
+            NamePattern::Glob(pattern) => glob_match(pattern.as_bytes(), name.as_bytes()),
+            NamePattern::Regex(regex) => regex.is_match(name),
+        }
+    }
+}
+
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match (pattern.first(), text.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => {
+            glob_match(&pattern[1..], text) || (!text.is_empty() && glob_match(pattern, &text[1..]))
+        }
+        (Some(b'?'), Some(_)) => glob_match(&pattern[1..], &text[1..]),
+        (Some(p), Some(t)) if p == t => glob_match(&pattern[1..], &text[1..]),
+        _ => false,
+    }
+}
+
+/// A query for [`DirectoryHandle::search`](crate::DirectoryHandle::search): a required filename
+/// pattern, plus an optional regex matched against file contents.
+pub struct SearchQuery {
+    pub name: NamePattern,
+    pub content: Option<regex::Regex>,
+}
+
+/// Bytes beyond this point in a file aren't consulted when guessing whether it's binary.
+const BINARY_SNIFF_LEN: usize = 8192;
+
+fn looks_binary(data: &[u8]) -> bool {
+    data[..data.len().min(BINARY_SNIFF_LEN)].contains(&0)
+}
+
+type PendingEntry<D> = Result<(PathBuf, DirectoryEntry<D, <D as DirectoryHandle>::FileHandleT>), <D as DirectoryHandle>::Error>;
+
+/// Lists one directory, queueing its subdirectories for further descent and buffering its
+/// entries (files and, depth permitting, subdirectories) into `pending` for the caller to drain.
+/// Only this directory's own listing failure is propagated as an `Err` return; a failure to stat
+/// a child while deciding whether it's a symlink is instead folded into `pending` as an `Err`
+/// item, since the rest of the listing is still good.
+async fn process_dir<D: DirectoryHandle>(
+    prefix: PathBuf,
+    depth: usize,
+    dir: D,
+    options: &WalkOptions,
+    queue: &mut VecDeque<(PathBuf, usize, D)>,
+    pending: &mut VecDeque<PendingEntry<D>>,
+) -> Result<(), D::Error> {
+    let entries = dir.entries().await?;
+    pin_mut!(entries);
+
+    while let Some(entry) = entries.next().await {
+        match entry {
+            Ok((name, DirectoryEntry::Directory(child))) => {
+                let path = prefix.join(&name);
+                let within_depth = !options.max_depth.is_some_and(|max_depth| depth >= max_depth);
+                if within_depth {
+                    let metadata = match child.metadata().await {
+                        Ok(metadata) => metadata,
+                        Err(error) => {
+                            pending.push_back(Err(error));
+                            continue;
+                        }
+                    };
+                    if options.follow_symlinks || !metadata.file_type.is_symlink() {
+                        queue.push_back((path.clone(), depth + 1, child.clone()));
+                    }
+                    pending.push_back(Ok((path, DirectoryEntry::Directory(child))));
+                }
+            }
+            Ok((name, DirectoryEntry::File(file))) => {
+                pending.push_back(Ok((prefix.join(&name), DirectoryEntry::File(file))));
+            }
+            Err(error) => pending.push_back(Err(error)),
+        }
+    }
+
+    Ok(())
+}
+
+/// Recursively descends `root`, yielding every descendant with its path relative to `root`.
+///
+/// This is an explicit work-queue traversal (push the root, pop a directory, list it, push its
+/// subdirectories back on the queue) rather than `async fn` recursion, so it stays iterative and
+/// isn't bounded by the compiler's recursion-in-async limitations. This is the `walkdir`-style
+/// traversal that [`search`], [`DirectoryHandle::recursive_size`] and
+/// [`DirectoryHandle::export_tar`] are built on top of.
+///
+/// The root is listed eagerly, up front, so a bad root still surfaces as this function's
+/// `Result`; everything past that is driven lazily by [`futures::stream::unfold`], one queued
+/// directory at a time, so a caller only pays for as much of the tree as they actually consume
+/// instead of the whole tree being buffered into memory before the first item comes out. A
+/// failure to list a queued (non-root) directory is yielded as an `Err` item rather than aborting
+/// the stream, since there's no way to hand it back through this function's `Result` by the time
+/// it's discovered.
+pub async fn walk<D: DirectoryHandle>(
+    root: &D,
+    options: &WalkOptions,
+) -> Result<impl Stream<Item = PendingEntry<D>>, D::Error> {
+    let mut queue: VecDeque<(PathBuf, usize, D)> = VecDeque::new();
+    let mut pending: VecDeque<PendingEntry<D>> = VecDeque::new();
+
+    process_dir(PathBuf::new(), 0, root.clone(), options, &mut queue, &mut pending).await?;
+
+    Ok(futures::stream::unfold((queue, pending, options), |(mut queue, mut pending, options)| async move {
+        loop {
+            if let Some(item) = pending.pop_front() {
+                return Some((item, (queue, pending, options)));
+            }
+
+            let (prefix, depth, dir) = queue.pop_front()?;
+            if let Err(error) = process_dir(prefix, depth, dir, options, &mut queue, &mut pending).await {
+                return Some((Err(error), (queue, pending, options)));
+            }
+        }
+    }))
+}
+
+/// Searches `root` for files matching `query`, streaming matches as they're found so callers
+/// can consume results incrementally on huge trees.
+pub async fn search<D: DirectoryHandle>(
+    root: &D,
+    query: &SearchQuery,
+) -> Result<impl Stream<Item = Result<(PathBuf, D::FileHandleT), D::Error>>, D::Error> {
+    let entries = Box::pin(walk(root, &WalkOptions::default()).await?);
+
+    Ok(futures::stream::unfold((entries, query), |(mut entries, query)| async move {
+        loop {
+            let (path, file) = match entries.next().await? {
+                Ok((path, DirectoryEntry::File(file))) => (path, file),
+                Ok((_, DirectoryEntry::Directory(_))) => continue,
+                Err(error) => return Some((Err(error), (entries, query))),
+            };
+
+            let name_matches = path.file_name().and_then(|n| n.to_str()).is_some_and(|n| query.name.matches(n));
+            if !name_matches {
+                continue;
+            }
+
+            if let Some(content_pattern) = &query.content {
+                let data = match file.read().await {
+                    Ok(data) => data,
+                    Err(error) => return Some((Err(error), (entries, query))),
+                };
+
+                if looks_binary(&data) {
+                    continue;
+                }
+
+                if !content_pattern.is_match(&String::from_utf8_lossy(&data)) {
+                    continue;
+                }
+            }
+
+            return Some((Ok((path, file)), (entries, query)));
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::DirectoryHandle;
+    use crate::{DirectoryHandle as _, FileHandle as _, GetDirectoryHandleOptions, GetFileHandleOptions, WritableFileStream as _};
+
+    async fn write_file(dir: &DirectoryHandle, name: &str, contents: &[u8]) {
+        let mut file = dir
+            .get_file_handle_with_options(name, &GetFileHandleOptions { create: true })
+            .await
+            .unwrap();
+        let mut writer = file.create_writable().await.unwrap();
+        writer.write_at_cursor_pos(contents.to_vec()).await.unwrap();
+        writer.close().await.unwrap();
+    }
+
+    async fn build_tree() -> DirectoryHandle {
+        let root = DirectoryHandle::default();
+        write_file(&root, "a.txt", b"hello from root").await;
+
+        let sub = root
+            .get_directory_handle_with_options("sub", &GetDirectoryHandleOptions { create: true })
+            .await
+            .unwrap();
+        write_file(&sub, "b.txt", b"hello from sub").await;
+        write_file(&sub, "c.log", b"not it").await;
+
+        root
+    }
+
+    #[tokio::test]
+    async fn test_walk_lists_nested_files_and_dirs() {
+        let root = build_tree().await;
+
+        let stream = walk(&root, &WalkOptions::default()).await.unwrap();
+        let mut paths: Vec<_> = stream
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .map(|r| r.unwrap().0)
+            .collect();
+        paths.sort();
+
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("a.txt"),
+                PathBuf::from("sub"),
+                PathBuf::from("sub/b.txt"),
+                PathBuf::from("sub/c.log"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_walk_respects_max_depth() {
+        let root = build_tree().await;
+
+        let stream = walk(&root, &WalkOptions { max_depth: Some(0), follow_symlinks: false })
+            .await
+            .unwrap();
+        let paths: Vec<_> = stream.collect::<Vec<_>>().await.into_iter().map(|r| r.unwrap().0).collect();
+
+        assert_eq!(paths, vec![PathBuf::from("a.txt")]);
+    }
+
+    #[tokio::test]
+    async fn test_recursive_size_sums_files_across_the_subtree() {
+        let root = build_tree().await;
+
+        let size = crate::DirectoryHandle::recursive_size(&root).await.unwrap();
+        assert_eq!(size, "hello from root".len() as u64 + "hello from sub".len() as u64 + "not it".len() as u64);
+    }
+
+    #[tokio::test]
+    async fn test_search_propagates_file_read_error_as_the_directory_error_type() {
+        // Regression test: search() yields a file-read error (D::FileHandleT::Error) as an item
+        // of its Stream<Item = Result<_, D::Error>>, which only type-checks because
+        // DirectoryHandle::FileHandleT ties its Error back to the outer trait's Error. Here every
+        // read succeeds, so the test checks that the content-matching path runs without errors.
+        let root = build_tree().await;
+        let query = SearchQuery {
+            name: NamePattern::Glob("*".to_string()),
+            content: Some(regex::Regex::new(".").unwrap()),
+        };
+        let stream = search(&root, &query).await.unwrap();
+        let results: Vec<_> = stream.collect::<Vec<_>>().await;
+        assert!(results.iter().all(|r| r.is_ok()));
+    }
+
+    #[tokio::test]
+    async fn test_search_matches_by_glob_and_content() {
+        let root = build_tree().await;
+
+        let query = SearchQuery {
+            name: NamePattern::Glob("*.txt".to_string()),
+            content: Some(regex::Regex::new("sub").unwrap()),
+        };
+        let stream = search(&root, &query).await.unwrap();
+        let mut paths: Vec<_> = stream.collect::<Vec<_>>().await.into_iter().map(|r| r.unwrap().0).collect();
+        paths.sort();
+
+        assert_eq!(paths, vec![PathBuf::from("sub/b.txt")]);
+    }
+}
@@ -1,13 +1,19 @@
+use bytes::Bytes;
+use crate::FileHandle as _;
 use futures::Stream;
 use futures::StreamExt;
 use js_sys::{ArrayBuffer, AsyncIterator, Uint8Array};
 use wasm_bindgen::{JsCast, JsValue};
 use wasm_bindgen_futures::{JsFuture, stream::JsStream};
 use web_sys::{
-    FileSystemCreateWritableOptions, FileSystemDirectoryHandle, FileSystemFileHandle,
-    FileSystemGetFileOptions, FileSystemWritableFileStream,
+    File, FileSystemCreateWritableOptions, FileSystemDirectoryHandle, FileSystemFileHandle,
+    FileSystemGetFileOptions, FileSystemReadWriteOptions, FileSystemRemoveOptions,
+    FileSystemSyncAccessHandle, FileSystemWritableFileStream,
 };
 
+/// Chunk size used by [`FileHandle::read_stream`](crate::FileHandle::read_stream).
+const READ_STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
 type DirectoryEntry = crate::DirectoryEntry<DirectoryHandle, FileHandle>;
 
 #[derive(Debug, Clone)]
@@ -82,6 +88,112 @@ impl crate::DirectoryHandle for DirectoryHandle {
         Ok(DirectoryHandle(file_system_directory_handle))
     }
 
+    async fn metadata(&self) -> Result<crate::Metadata, Self::Error> {
+        // OPFS exposes no metadata API for directory handles themselves.
+        Ok(crate::Metadata {
+            len: 0,
+            file_type: crate::FileType::Directory,
+            created: None,
+            modified: None,
+            accessed: None,
+            readonly: false,
+        })
+    }
+
+    async fn rename_entry(
+        &mut self,
+        from: &str,
+        to: &Self,
+        new_name: &str,
+    ) -> Result<(), Self::Error> {
+        let options = crate::CopyOptions {
+            overwrite: true,
+            recursive: true,
+        };
+        self.copy_entry(from, to, new_name, &options).await?;
+
+        // Use the underlying JS API directly (rather than `remove_entry`) so a directory
+        // source is removed recursively; the crate-level `remove_entry` has no recursive option.
+        let remove_options = FileSystemRemoveOptions::new();
+        remove_options.set_recursive(true);
+        JsFuture::from(self.0.remove_entry_with_options(from, &remove_options)).await?;
+        Ok(())
+    }
+
+    async fn copy_entry(
+        &mut self,
+        name: &str,
+        dest: &Self,
+        new_name: &str,
+        options: &crate::CopyOptions,
+    ) -> Result<(), Self::Error> {
+        // OPFS has no generic "stat"; probe for a file handle first and fall back to a
+        // directory handle if that fails.
+        let file_probe = JsFuture::from(
+            self.0
+                .get_file_handle_with_options(name, &FileSystemGetFileOptions::new()),
+        )
+        .await;
+
+        if let Ok(file_handle) = file_probe {
+            let file = FileHandle(FileSystemFileHandle::from(file_handle));
+
+            if !options.overwrite {
+                let exists = JsFuture::from(
+                    dest.0
+                        .get_file_handle_with_options(new_name, &FileSystemGetFileOptions::new()),
+                )
+                .await;
+                if exists.is_ok() {
+                    return Err(JsValue::from_str(&format!(
+                        "'{new_name}' already exists"
+                    )));
+                }
+            }
+
+            let data = file.read().await?;
+            let get_options = FileSystemGetFileOptions::new();
+            get_options.set_create(true);
+            let dest_file_system_handle = FileSystemFileHandle::from(
+                JsFuture::from(dest.0.get_file_handle_with_options(new_name, &get_options)).await?,
+            );
+            let mut dest_file = FileHandle(dest_file_system_handle);
+            let mut writer = dest_file.create_writable().await?;
+            crate::WritableFileStream::write_at_cursor_pos(&mut writer, data).await?;
+            crate::WritableFileStream::close(&mut writer).await?;
+            return Ok(());
+        }
+
+        if !options.recursive {
+            return Err(JsValue::from_str(&format!(
+                "'{name}' is a directory; set CopyOptions::recursive to copy it"
+            )));
+        }
+
+        use web_sys::FileSystemGetDirectoryOptions;
+
+        let src_dir_handle = FileSystemDirectoryHandle::from(
+            JsFuture::from(
+                self.0
+                    .get_directory_handle_with_options(name, &FileSystemGetDirectoryOptions::new()),
+            )
+            .await?,
+        );
+        let get_dir_options = FileSystemGetDirectoryOptions::new();
+        get_dir_options.set_create(true);
+        let dest_dir_handle = FileSystemDirectoryHandle::from(
+            JsFuture::from(dest.0.get_directory_handle_with_options(new_name, &get_dir_options))
+                .await?,
+        );
+
+        copy_dir_tree(
+            DirectoryHandle(src_dir_handle),
+            DirectoryHandle(dest_dir_handle),
+            options.overwrite,
+        )
+        .await
+    }
+
     async fn remove_entry(&mut self, name: &str) -> Result<(), Self::Error> {
         JsFuture::from(self.0.remove_entry(name)).await?;
         Ok(())
@@ -125,6 +237,78 @@ impl crate::DirectoryHandle for DirectoryHandle {
 
         Ok(stream)
     }
+
+    async fn watch_with_options(
+        &self,
+        _options: &crate::WatchOptions,
+    ) -> Result<impl Stream<Item = crate::ChangeEvent> + 'static, Self::Error> {
+        // OPFS has no observer API yet; revisit once FileSystemObserver lands.
+        Err::<futures::stream::Empty<crate::ChangeEvent>, _>(JsValue::from_str(
+            "watch() is not supported by the web backend yet",
+        ))
+    }
+}
+
+/// Recursively copies every entry under `src` into `dest`, using a work queue rather than
+/// recursive `async fn` calls (which the compiler can't size).
+async fn copy_dir_tree(
+    src: DirectoryHandle,
+    dest: DirectoryHandle,
+    overwrite: bool,
+) -> Result<(), JsValue> {
+    use crate::DirectoryHandle as _;
+
+    let mut queue = std::collections::VecDeque::new();
+    queue.push_back((src, dest));
+
+    while let Some((src_dir, dest_dir)) = queue.pop_front() {
+        let entries = src_dir.entries().await?;
+        futures::pin_mut!(entries);
+
+        while let Some(entry) = entries.next().await {
+            let (name, entry) = entry?;
+            match entry {
+                DirectoryEntry::File(file) => {
+                    if !overwrite {
+                        let exists = JsFuture::from(
+                            dest_dir
+                                .0
+                                .get_file_handle_with_options(&name, &FileSystemGetFileOptions::new()),
+                        )
+                        .await;
+                        if exists.is_ok() {
+                            return Err(JsValue::from_str(&format!(
+                                "'{name}' already exists"
+                            )));
+                        }
+                    }
+
+                    let data = file.read().await?;
+                    let get_options = FileSystemGetFileOptions::new();
+                    get_options.set_create(true);
+                    let dest_file_system_handle = FileSystemFileHandle::from(
+                        JsFuture::from(
+                            dest_dir.0.get_file_handle_with_options(&name, &get_options),
+                        )
+                        .await?,
+                    );
+                    let mut dest_file = FileHandle(dest_file_system_handle);
+                    let mut writer = dest_file.create_writable().await?;
+                    crate::WritableFileStream::write_at_cursor_pos(&mut writer, data).await?;
+                    crate::WritableFileStream::close(&mut writer).await?;
+                }
+                DirectoryEntry::Directory(child_src) => {
+                    let child_dest = dest_dir.get_directory_handle_with_options(
+                        &name,
+                        &crate::GetDirectoryHandleOptions { create: true },
+                    );
+                    queue.push_back((child_src, child_dest.await?));
+                }
+            }
+        }
+    }
+
+    Ok(())
 }
 
 impl crate::FileHandle for FileHandle {
@@ -143,14 +327,60 @@ impl crate::FileHandle for FileHandle {
         Ok(WritableFileStream(file_system_writable_file_stream))
     }
 
-    async fn read(&self) -> Result<Vec<u8>, Self::Error> {
-        self.get_file().await?.read().await
-    }
-
     async fn size(&self) -> Result<usize, Self::Error> {
         let size = self.get_file().await?.size();
         Ok(size)
     }
+
+    async fn read_range(&self, offset: usize, len: usize) -> Result<Vec<u8>, Self::Error> {
+        let blob = self.get_file().await?;
+        let size = blob.size();
+        if offset >= size {
+            return Ok(Vec::new());
+        }
+
+        let end = (offset + len).min(size);
+        blob.slice(offset, end)?.read().await
+    }
+
+    async fn read_stream(
+        &self,
+    ) -> Result<impl Stream<Item = Result<Bytes, Self::Error>>, Self::Error> {
+        let blob = self.get_file().await?;
+        let size = blob.size();
+
+        Ok(futures::stream::try_unfold((blob, 0usize), move |(blob, offset)| async move {
+            if offset >= size {
+                return Ok(None);
+            }
+            let end = (offset + READ_STREAM_CHUNK_SIZE).min(size);
+            let chunk = blob.slice(offset, end)?.read().await?;
+            Ok::<_, JsValue>(Some((Bytes::from(chunk), (blob, end))))
+        }))
+    }
+
+    async fn metadata(&self) -> Result<crate::Metadata, Self::Error> {
+        let file: File = JsFuture::from(self.0.get_file()).await?.into();
+
+        let modified = std::time::SystemTime::UNIX_EPOCH
+            .checked_add(std::time::Duration::from_millis(file.last_modified() as u64));
+
+        Ok(crate::Metadata {
+            len: file.size() as u64,
+            file_type: crate::FileType::File,
+            created: None,
+            modified,
+            accessed: None,
+            // OPFS has no permission bits to read back.
+            readonly: false,
+        })
+    }
+
+    async fn set_readonly(&mut self, _readonly: bool) -> Result<(), Self::Error> {
+        Err(JsValue::from_str(
+            "set_readonly() is not supported by the web backend; OPFS has no permission bits",
+        ))
+    }
 }
 
 impl FileHandle {
@@ -158,6 +388,95 @@ impl FileHandle {
         let file: web_sys::Blob = JsFuture::from(self.0.get_file()).await?.into();
         Ok(Blob(file))
     }
+
+    /// Opens a synchronous, random-access handle to this file for low-latency reads/writes.
+    /// Only callable inside a Web Worker; OPFS refuses to hand one out on the main thread.
+    pub async fn create_sync_access_handle(&self) -> Result<SyncAccessHandle, JsValue> {
+        let handle = FileSystemSyncAccessHandle::unchecked_from_js(
+            JsFuture::from(self.0.create_sync_access_handle()).await?,
+        );
+        Ok(SyncAccessHandle(handle))
+    }
+
+    /// Reads the file as blob slices of a caller-chosen size instead of
+    /// [`READ_STREAM_CHUNK_SIZE`]. Unlike [`crate::FileHandle::read_stream`], every slice is read
+    /// before the stream is returned, so the whole file is held in memory as chunks; `chunk_size`
+    /// bounds only the size of each single allocation.
+    pub async fn read_stream_with_chunk_size(
+        &self,
+        chunk_size: usize,
+    ) -> Result<impl Stream<Item = Result<Bytes, JsValue>>, JsValue> {
+        let chunk_size = chunk_size.max(1);
+        let blob = self.get_file().await?;
+        let size = blob.size();
+
+        let mut chunks = Vec::new();
+        let mut offset = 0;
+        while offset < size {
+            let end = (offset + chunk_size).min(size);
+            let chunk = blob.slice(offset, end)?.read().await?;
+            chunks.push(Ok(Bytes::from(chunk)));
+            offset = end;
+        }
+        Ok(futures::stream::iter(chunks))
+    }
+}
+
+/// A blocking, random-access file handle obtained via [`FileHandle::create_sync_access_handle`].
+/// Unlike every other read/write path in this module, its methods are genuinely synchronous JS
+/// calls rather than `Promise`-returning ones, which is what makes it fast enough for
+/// low-latency random access.
+#[derive(Debug)]
+pub struct SyncAccessHandle(FileSystemSyncAccessHandle);
+
+impl SyncAccessHandle {
+    /// Reads into `buffer` starting at `offset`, returning the number of bytes actually read.
+    pub fn read_at(&self, buffer: &mut [u8], offset: u64) -> std::io::Result<usize> {
+        let options = FileSystemReadWriteOptions::new();
+        options.set_at(offset as f64);
+        self.0
+            .read_with_u8_array_and_options(buffer, &options)
+            .map(|n| n as usize)
+            .map_err(|e| std::io::Error::other(format!("{e:?}")))
+    }
+
+    /// Writes `buffer` starting at `offset`, returning the number of bytes actually written.
+    pub fn write_at(&self, buffer: &[u8], offset: u64) -> std::io::Result<usize> {
+        let options = FileSystemReadWriteOptions::new();
+        options.set_at(offset as f64);
+        self.0
+            .write_with_u8_array_and_options(buffer, &options)
+            .map(|n| n as usize)
+            .map_err(|e| std::io::Error::other(format!("{e:?}")))
+    }
+
+    /// Resizes the file to exactly `size` bytes.
+    pub fn truncate(&self, size: u64) -> std::io::Result<()> {
+        self.0
+            .truncate_with_f64(size as f64)
+            .map_err(|e| std::io::Error::other(format!("{e:?}")))
+    }
+
+    pub fn size(&self) -> std::io::Result<u64> {
+        self.0
+            .get_size()
+            .map(|n| n as u64)
+            .map_err(|e| std::io::Error::other(format!("{e:?}")))
+    }
+
+    /// Persists writes made so far to disk without closing the handle.
+    pub fn flush(&self) -> std::io::Result<()> {
+        self.0
+            .flush()
+            .map_err(|e| std::io::Error::other(format!("{e:?}")))
+    }
+
+    /// Releases the lock this handle holds on the underlying file, allowing other handles
+    /// (sync or async) to access it again.
+    pub fn close(self) -> std::io::Result<()> {
+        self.0.close();
+        Ok(())
+    }
 }
 
 impl crate::WritableFileStream for WritableFileStream {
@@ -177,6 +496,58 @@ impl crate::WritableFileStream for WritableFileStream {
         JsFuture::from(self.0.seek_with_u32(offset as u32)?).await?;
         Ok(())
     }
+
+    async fn truncate(&mut self, size: usize) -> Result<(), Self::Error> {
+        JsFuture::from(self.0.truncate_with_u32(size as u32)?).await?;
+        Ok(())
+    }
+}
+
+/// A scratch file in a dedicated `.tmp` subdirectory of some root `DirectoryHandle`, removed
+/// automatically when dropped.
+///
+/// `Drop` can't be `async`, so cleanup is handed off to a spawned task via
+/// `wasm_bindgen_futures::spawn_local` rather than awaited inline; a `TempFile` dropped outside
+/// a wasm event loop (e.g. during teardown) simply leaves its removal unobserved, same as any
+/// other fire-and-forget spawned task.
+pub struct TempFile {
+    tmp_dir: DirectoryHandle,
+    name: String,
+}
+
+impl TempFile {
+    /// Creates a uniquely-named file inside a `.tmp` subdirectory of `root` (created if it
+    /// doesn't exist yet) and hands back a handle to it alongside the `TempFile` guard that
+    /// removes it on drop.
+    pub async fn create(root: &DirectoryHandle) -> Result<(Self, FileHandle), JsValue> {
+        use crate::DirectoryHandle as _;
+
+        let tmp_dir = root
+            .get_directory_handle_with_options(
+                ".tmp",
+                &crate::GetDirectoryHandleOptions { create: true },
+            )
+            .await?;
+
+        let name = format!("tmp-{:016x}", (js_sys::Math::random() * u64::MAX as f64) as u64);
+        let file = tmp_dir
+            .get_file_handle_with_options(&name, &crate::GetFileHandleOptions { create: true })
+            .await?;
+
+        Ok((TempFile { tmp_dir, name }, file))
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        use crate::DirectoryHandle as _;
+
+        let mut tmp_dir = self.tmp_dir.clone();
+        let name = self.name.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            let _ = tmp_dir.remove_entry(&name).await;
+        });
+    }
 }
 
 impl Blob {
@@ -184,6 +555,12 @@ impl Blob {
         self.0.size() as usize
     }
 
+    fn slice(&self, start: usize, end: usize) -> Result<Blob, JsValue> {
+        Ok(Blob(
+            self.0.slice_with_i32_and_i32(start as i32, end as i32)?,
+        ))
+    }
+
     async fn read(&self) -> Result<Vec<u8>, JsValue> {
         let buffer = ArrayBuffer::unchecked_from_js(JsFuture::from(self.0.array_buffer()).await?);
         let uint8_array = Uint8Array::new(&buffer);